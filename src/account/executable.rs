@@ -0,0 +1,117 @@
+//! Inspecting program executables (ELFs): the embedded security.txt and the
+//! hash used by verified builds
+
+use std::collections::BTreeMap;
+
+use goblin::elf::Elf;
+use prettytable::{row, Table};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::utils::{insert_newlines, to_hex};
+
+const SECURITY_TXT_SECTION: &str = ".security.txt";
+const SECURITY_TXT_BEGIN: &[u8] = b"=======BEGIN SECURITY.TXT V1=======\0";
+const SECURITY_TXT_END: &[u8] = b"=======END SECURITY.TXT V1=======\0";
+
+/// Known security.txt fields and their labels, in display order
+const SECURITY_TXT_FIELDS: &[(&str, &str)] = &[
+    ("name", "Name"),
+    ("project_url", "Project URL"),
+    ("contacts", "Contacts"),
+    ("policy", "Policy"),
+    ("preferred_languages", "Preferred Languages"),
+    ("source_code", "Source Code"),
+    ("source_release", "Source Release"),
+    ("source_revision", "Source Revision"),
+    ("encryption", "Encryption"),
+    ("auditors", "Auditors"),
+    ("acknowledgements", "Acknowledgements"),
+    ("expiry", "Expiry"),
+];
+
+pub struct Executable {
+    /// Hex encoded SHA-256 of the executable, with trailing zero padding
+    /// removed. This matches the hash reported by `solana-verify`.
+    pub hash: String,
+    pub security_txt: Option<SecurityTxt>,
+}
+
+impl Executable {
+    pub fn parse(elf: &[u8]) -> Executable {
+        Executable {
+            hash: program_hash(elf),
+            security_txt: SecurityTxt::from_elf(elf),
+        }
+    }
+}
+
+/// The security.txt embedded by the `solana-security-txt` crate's
+/// `security_txt!` macro, as key-value pairs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SecurityTxt(pub BTreeMap<String, String>);
+
+impl SecurityTxt {
+    /// Extracts the security.txt from the ELF's `.security.txt` section. Returns
+    /// `None` if the ELF is invalid or has no (well-formed) security.txt.
+    pub fn from_elf(elf: &[u8]) -> Option<SecurityTxt> {
+        let parsed = Elf::parse(elf).ok()?;
+        let section = parsed.section_headers.iter().find(|header| {
+            parsed.shdr_strtab.get_at(header.sh_name) == Some(SECURITY_TXT_SECTION)
+        })?;
+
+        let start = usize::try_from(section.sh_offset).ok()?;
+        let end = start.checked_add(usize::try_from(section.sh_size).ok()?)?;
+        SecurityTxt::parse(elf.get(start..end)?)
+    }
+
+    /// Parses the null separated key-value pairs between the begin and end
+    /// markers
+    fn parse(section: &[u8]) -> Option<SecurityTxt> {
+        let contents = section.strip_prefix(SECURITY_TXT_BEGIN)?;
+        let end = contents
+            .windows(SECURITY_TXT_END.len())
+            .position(|window| window == SECURITY_TXT_END)?;
+
+        // Every key and value is terminated by a null byte
+        let mut parts = contents[..end]
+            .split(|&byte| byte == 0)
+            .map(|part| String::from_utf8_lossy(part).into_owned());
+        let mut fields = BTreeMap::new();
+        while let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            fields.insert(key, value);
+        }
+
+        Some(SecurityTxt(fields))
+    }
+
+    pub fn table(&self, width: usize) -> Table {
+        let mut table = Table::new();
+        table.set_titles(row![c->"Security.txt", ""]);
+
+        let known = SECURITY_TXT_FIELDS
+            .iter()
+            .filter_map(|&(key, label)| Some((label, self.0.get(key)?)));
+        let unknown = self
+            .0
+            .iter()
+            .filter(|(key, _)| SECURITY_TXT_FIELDS.iter().all(|(known, _)| known != key))
+            .map(|(key, value)| (key.as_str(), value));
+
+        for (label, value) in known.chain(unknown) {
+            table.add_row(row![c->label, insert_newlines(value, width.max(1))]);
+        }
+
+        table
+    }
+}
+
+/// SHA-256 of the executable with trailing zeros trimmed, since program data
+/// accounts are usually allocated larger than the deployed ELF
+pub fn program_hash(elf: &[u8]) -> String {
+    let len = elf
+        .iter()
+        .rposition(|&byte| byte != 0)
+        .map_or(0, |idx| idx + 1);
+    to_hex(&Sha256::digest(&elf[..len]))
+}
@@ -0,0 +1,129 @@
+//! Parsing address lookup table accounts
+
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
+use solana_sdk::{
+    account::Account,
+    address_lookup_table::{self, state::AddressLookupTable},
+    clock::Slot,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::{ExplorerError, Result},
+    utils::display_balance,
+};
+
+use super::ParsedAccount;
+
+pub struct LookupTableAccount<'a> {
+    pub lamports: u64,
+    pub table: AddressLookupTable<'a>,
+}
+
+impl<'a> LookupTableAccount<'a> {
+    pub fn parse(account: &'a Account) -> Result<Option<ParsedAccount<'a>>> {
+        if account.owner != address_lookup_table::program::ID {
+            return Ok(None);
+        }
+
+        let table = match AddressLookupTable::deserialize(&account.data) {
+            Ok(table) => table,
+            // Nothing to show beyond the raw account
+            Err(InstructionError::UninitializedAccount) => return Ok(None),
+            Err(e) => return Err(ExplorerError::Decode(format!("address lookup table: {e}"))),
+        };
+
+        Ok(Some(ParsedAccount::LookupTable(Box::new(
+            LookupTableAccount {
+                lamports: account.lamports,
+                table,
+            },
+        ))))
+    }
+
+    pub fn display(self, key: &Pubkey) {
+        let meta = &self.table.meta;
+
+        let mut table_table = Table::new();
+        table_table.set_titles(row![c->"Address Lookup Table", key]);
+        table_table.add_row(row![c->"SOL Balance", display_balance(self.lamports, 9)]);
+        table_table.add_row(row![
+            c->"Authority",
+            meta.authority
+                .map(|authority| authority.to_string())
+                .unwrap_or("None (frozen)".to_string())
+        ]);
+        table_table.add_row(row![
+            c->"Deactivation Slot",
+            format_deactivation_slot(meta.deactivation_slot)
+        ]);
+        table_table.add_row(row![c->"Last Extended Slot", meta.last_extended_slot]);
+        table_table.add_row(row![
+            c->"Last Extended Start Index",
+            meta.last_extended_slot_start_index
+        ]);
+        table_table.add_row(row![c->"Addresses", self.table.addresses.len()]);
+
+        let mut addresses_table = Table::new();
+        addresses_table.set_titles(row![c->"Index", c->"Address"]);
+        for (idx, address) in self.table.addresses.iter().enumerate() {
+            addresses_table.add_row(row![r->idx, address]);
+        }
+
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+        let padded_width = width.saturating_sub(4);
+
+        let mut tables = Table::new();
+        tables.add_row(row![c->table_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+        if !self.table.addresses.is_empty() {
+            tables.add_row(row![c->addresses_table]);
+            tables.add_row(row![" ".repeat(padded_width)]);
+        }
+        tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+        tables.printstd();
+    }
+
+    pub fn to_view(&self) -> LookupTableView {
+        let meta = &self.table.meta;
+        LookupTableView {
+            authority: meta.authority.as_ref().map(ToString::to_string),
+            deactivation_slot: (meta.deactivation_slot != Slot::MAX)
+                .then_some(meta.deactivation_slot),
+            last_extended_slot: meta.last_extended_slot,
+            last_extended_slot_start_index: meta.last_extended_slot_start_index,
+            addresses: self
+                .table
+                .addresses
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// Serializable view model of a [`LookupTableAccount`]. A `null` authority
+/// means the table is frozen, and a `null` deactivation slot means it is
+/// active.
+#[derive(Serialize)]
+pub struct LookupTableView {
+    pub authority: Option<String>,
+    pub deactivation_slot: Option<Slot>,
+    pub last_extended_slot: Slot,
+    pub last_extended_slot_start_index: u8,
+    /// Addresses in index order
+    pub addresses: Vec<String>,
+}
+
+/// Active tables have a deactivation slot of `Slot::MAX`
+fn format_deactivation_slot(slot: Slot) -> String {
+    if slot == Slot::MAX {
+        "None (active)".to_string()
+    } else {
+        slot.to_string()
+    }
+}
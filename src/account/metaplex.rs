@@ -0,0 +1,333 @@
+//! Fetching Metaplex token metadata and edition accounts for a mint
+
+use mpl_token_metadata::{
+    accounts::{Edition, MasterEdition, Metadata},
+    types::{CollectionDetails, Key, TokenStandard},
+};
+use std::collections::HashMap;
+
+use prettytable::{row, Table};
+use serde::Serialize;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient as Client, rpc_request::MAX_MULTIPLE_ACCOUNTS,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{error::Result, utils::format_basis_points};
+
+#[derive(Clone)]
+pub struct MetaplexMetadata {
+    /// The metadata PDA
+    pub address: Pubkey,
+    pub metadata: Metadata,
+    pub edition: Option<EditionInfo>,
+}
+
+/// The account at the mint's edition PDA, which only exists for NFTs
+#[derive(Clone)]
+pub enum EditionInfo {
+    Master {
+        address: Pubkey,
+        supply: u64,
+        /// `None` if an unlimited number of prints can be made
+        max_supply: Option<u64>,
+    },
+    Print {
+        address: Pubkey,
+        /// The master edition this edition was printed from
+        parent: Pubkey,
+        edition: u64,
+    },
+}
+
+impl MetaplexMetadata {
+    /// Fetches the metadata and edition PDAs of mints in as few requests as
+    /// possible, keyed by mint. Mints without (decodable) metadata are left
+    /// out.
+    pub async fn fetch_many(
+        mints: &[Pubkey],
+        client: &Client,
+    ) -> Result<HashMap<Pubkey, MetaplexMetadata>> {
+        let mut fetched = HashMap::with_capacity(mints.len());
+        // Each mint has two PDAs
+        for mints in mints.chunks(MAX_MULTIPLE_ACCOUNTS / 2) {
+            let addresses: Vec<Pubkey> = mints
+                .iter()
+                .flat_map(|mint| [Metadata::find_pda(mint).0, MasterEdition::find_pda(mint).0])
+                .collect();
+            let accounts = client.get_multiple_accounts(&addresses).await?;
+
+            for ((mint, addresses), accounts) in mints
+                .iter()
+                .zip(addresses.chunks(2))
+                .zip(accounts.chunks(2))
+            {
+                if let Some(metadata) =
+                    MetaplexMetadata::from_accounts(addresses, [&accounts[0], &accounts[1]])
+                {
+                    fetched.insert(*mint, metadata);
+                }
+            }
+        }
+        Ok(fetched)
+    }
+
+    /// Decodes the accounts at the metadata and edition PDAs of a mint
+    fn from_accounts(
+        addresses: &[Pubkey],
+        [metadata, edition]: [&Option<Account>; 2],
+    ) -> Option<MetaplexMetadata> {
+        let metadata = metadata
+            .as_ref()
+            .filter(|account| account.owner == mpl_token_metadata::ID)
+            .and_then(|account| Metadata::from_bytes(&account.data).ok())?;
+
+        let edition = edition
+            .as_ref()
+            .filter(|account| account.owner == mpl_token_metadata::ID)
+            .and_then(|account| EditionInfo::parse(addresses[1], &account.data));
+
+        Some(MetaplexMetadata {
+            address: addresses[0],
+            metadata: Metadata {
+                name: trim_padding(&metadata.name),
+                symbol: trim_padding(&metadata.symbol),
+                uri: trim_padding(&metadata.uri),
+                ..metadata
+            },
+            edition,
+        })
+    }
+
+    pub fn table(&self) -> Table {
+        let metadata = &self.metadata;
+
+        let mut metadata_table = Table::new();
+        metadata_table.set_titles(row![c->"Metaplex Metadata", self.address]);
+        metadata_table.add_row(row![c->"Name", metadata.name]);
+        metadata_table.add_row(row![c->"Symbol", metadata.symbol]);
+        metadata_table.add_row(row![c->"URI", metadata.uri]);
+        metadata_table.add_row(row![c->"Update Authority", metadata.update_authority]);
+        metadata_table.add_row(row![c->"Mutable", metadata.is_mutable]);
+        metadata_table.add_row(row![c->"Primary Sale Happened", metadata.primary_sale_happened]);
+        metadata_table.add_row(row![
+            c->"Seller Fee",
+            format_basis_points(metadata.seller_fee_basis_points as i64)
+        ]);
+        if let Some(token_standard) = &metadata.token_standard {
+            metadata_table
+                .add_row(row![c->"Token Standard", token_standard_name(token_standard).0]);
+        }
+        if let Some(collection) = &metadata.collection {
+            let verified = if collection.verified {
+                "verified"
+            } else {
+                "unverified"
+            };
+            metadata_table
+                .add_row(row![c->"Collection", format!("{} ({verified})", collection.key)]);
+        }
+        if let Some(size) = self.collection_size() {
+            metadata_table.add_row(row![c->"Collection Size", size]);
+        }
+        match &self.edition {
+            Some(EditionInfo::Master {
+                address,
+                supply,
+                max_supply,
+            }) => {
+                let max_supply = max_supply.map_or("unlimited".to_string(), |max| max.to_string());
+                metadata_table.add_row(row![c->"Master Edition", address]);
+                metadata_table.add_row(row![c->"Prints", format!("{supply} / {max_supply}")]);
+            }
+            Some(EditionInfo::Print {
+                address,
+                parent,
+                edition,
+            }) => {
+                metadata_table.add_row(row![c->"Print Edition", address]);
+                metadata_table.add_row(row![c->"Edition Number", edition]);
+                metadata_table.add_row(row![c->"Parent Edition", parent]);
+            }
+            None => {}
+        }
+
+        // Creators are nested, as there can be up to five of them
+        if let Some(creators) = metadata.creators.as_ref().filter(|c| !c.is_empty()) {
+            let mut creators_table = Table::new();
+            creators_table.set_titles(row![c->"Address", c->"Verified", c->"Share"]);
+            for creator in creators {
+                creators_table.add_row(row![
+                    creator.address,
+                    c->creator.verified,
+                    r->format!("{}%", creator.share)
+                ]);
+            }
+            metadata_table.add_row(row![c->"Creators", creators_table]);
+        }
+
+        metadata_table
+    }
+
+    fn collection_size(&self) -> Option<u64> {
+        match self.metadata.collection_details {
+            Some(CollectionDetails::V1 { size }) => Some(size),
+            _ => None,
+        }
+    }
+
+    pub fn to_view(&self) -> MetaplexMetadataView {
+        let metadata = &self.metadata;
+        MetaplexMetadataView {
+            address: self.address.to_string(),
+            update_authority: metadata.update_authority.to_string(),
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators: metadata
+                .creators
+                .iter()
+                .flatten()
+                .map(|creator| CreatorView {
+                    address: creator.address.to_string(),
+                    verified: creator.verified,
+                    share: creator.share,
+                })
+                .collect(),
+            primary_sale_happened: metadata.primary_sale_happened,
+            is_mutable: metadata.is_mutable,
+            token_standard: metadata
+                .token_standard
+                .as_ref()
+                .map(|standard| token_standard_name(standard).1),
+            collection: metadata
+                .collection
+                .as_ref()
+                .map(|collection| CollectionView {
+                    key: collection.key.to_string(),
+                    verified: collection.verified,
+                }),
+            collection_size: self.collection_size(),
+            edition: self.edition.as_ref().map(EditionInfo::to_view),
+        }
+    }
+}
+
+impl EditionInfo {
+    fn parse(address: Pubkey, data: &[u8]) -> Option<EditionInfo> {
+        // The first byte tells which kind of edition lives at the PDA
+        match *data.first()? {
+            key if key == Key::MasterEditionV1 as u8 || key == Key::MasterEditionV2 as u8 => {
+                let master = MasterEdition::from_bytes(data).ok()?;
+                Some(EditionInfo::Master {
+                    address,
+                    supply: master.supply,
+                    max_supply: master.max_supply,
+                })
+            }
+            key if key == Key::EditionV1 as u8 => {
+                let edition = Edition::from_bytes(data).ok()?;
+                Some(EditionInfo::Print {
+                    address,
+                    parent: edition.parent,
+                    edition: edition.edition,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn to_view(&self) -> EditionView {
+        match self {
+            EditionInfo::Master {
+                address,
+                supply,
+                max_supply,
+            } => EditionView::Master {
+                address: address.to_string(),
+                supply: *supply,
+                max_supply: *max_supply,
+            },
+            EditionInfo::Print {
+                address,
+                parent,
+                edition,
+            } => EditionView::Print {
+                address: address.to_string(),
+                parent: parent.to_string(),
+                edition: *edition,
+            },
+        }
+    }
+}
+
+/// Serializable view model of [`MetaplexMetadata`]
+#[derive(Serialize)]
+pub struct MetaplexMetadataView {
+    pub address: String,
+    pub update_authority: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CreatorView>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub token_standard: Option<&'static str>,
+    pub collection: Option<CollectionView>,
+    /// Only set on collection NFTs
+    pub collection_size: Option<u64>,
+    pub edition: Option<EditionView>,
+}
+
+#[derive(Serialize)]
+pub struct CreatorView {
+    pub address: String,
+    pub verified: bool,
+    /// Percentage of the seller fee paid to this creator
+    pub share: u8,
+}
+
+#[derive(Serialize)]
+pub struct CollectionView {
+    pub key: String,
+    pub verified: bool,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EditionView {
+    Master {
+        address: String,
+        supply: u64,
+        max_supply: Option<u64>,
+    },
+    Print {
+        address: String,
+        parent: String,
+        edition: u64,
+    },
+}
+
+/// The display label and JSON name of a token standard
+fn token_standard_name(standard: &TokenStandard) -> (&'static str, &'static str) {
+    match standard {
+        TokenStandard::NonFungible => ("Non-Fungible", "non_fungible"),
+        TokenStandard::FungibleAsset => ("Fungible Asset", "fungible_asset"),
+        TokenStandard::Fungible => ("Fungible", "fungible"),
+        TokenStandard::NonFungibleEdition => ("Non-Fungible Edition", "non_fungible_edition"),
+        TokenStandard::ProgrammableNonFungible => {
+            ("Programmable Non-Fungible", "programmable_non_fungible")
+        }
+        TokenStandard::ProgrammableNonFungibleEdition => (
+            "Programmable Non-Fungible Edition",
+            "programmable_non_fungible_edition",
+        ),
+    }
+}
+
+/// Metadata strings are null padded to their maximum length
+fn trim_padding(s: &str) -> String {
+    s.trim_end_matches('\0').to_string()
+}
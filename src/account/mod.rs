@@ -1,65 +1,308 @@
 use std::io::Write;
 
 use base64::Engine;
+use colored::Colorize;
 use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
 use solana_client::nonblocking::rpc_client::RpcClient as Client;
 use solana_sdk::{account::Account, pubkey::Pubkey};
-use spl_token_2022::extension::ExtensionType;
 
-use crate::utils::{display_balance, get_network};
+use crate::{
+    error::{ExplorerError, Result},
+    program::write_dump,
+    utils::{display_balance, print_json, OutputFormat},
+};
 
 use self::{
+    lookup_table::{LookupTableAccount, LookupTableView},
+    metaplex::MetaplexMetadata,
+    nonce::{NonceAccount, NonceAccountView},
+    offchain::{FetchLimits, OffChainMetadata, OffChainMetadataView},
+    program::{
+        dump_data, BufferView, NonUpgradeableProgramView, ProgramDataView, ProgramView,
+        UpgradeableProgramAccount,
+    },
+    stake::{StakeAccount, StakeAccountView},
     system::SystemAccount,
-    token::{Token22Account, TokenProgramAccount, TokenkegAccount},
+    sysvar::{SysvarAccount, SysvarView},
+    token::{
+        MintAccountView, MultisigAccountView, PrefetchedMints, Token22Account, TokenAccountBalance,
+        TokenAccountView, TokenProgramAccount, TokenkegAccount,
+    },
+    token_extensions::TokenExtension,
+    vote::{VoteAccount, VoteAccountView},
 };
 
+pub mod executable;
+pub mod lookup_table;
+pub mod metaplex;
+pub mod nonce;
+pub mod offchain;
+pub mod program;
+pub mod stake;
 pub mod system;
+pub mod sysvar;
 pub mod token;
+pub mod token_extensions;
+pub mod vote;
 
-pub async fn handler(rpc_url: String, account: crate::Account) {
-    // Build RPC Client
-    let client = Client::new(get_network(&rpc_url));
-
+pub async fn handler(client: Client, output: OutputFormat, account: crate::Account) -> Result<()> {
     // Fetch account
-    let fetched_account: Account = client.get_account(&account.pubkey).await.unwrap();
+    let fetched_account: Account = fetch_account(&client, &account.pubkey).await?;
+
+    // Write data to a file instead of displaying the account
+    if let Some(path) = &account.dump {
+        let data = dump_data(&account.pubkey, fetched_account, &client).await?;
+        return write_dump(&account.pubkey, &data, path, output);
+    }
 
     // Parse account
-    let parsed_account = parse_account(&fetched_account, &account.pubkey, &client).await;
+    let parsed_account = parse_account(&fetched_account, &account.pubkey, &client).await?;
 
-    println!();
-    parsed_account.display(&account.pubkey);
-    println!();
+    // Optionally fetch the off-chain metadata of the token's mint
+    let offchain_metadata = match (account.fetch_uri, parsed_account.metadata_uri()) {
+        (true, Some(uri)) => Some(OffChainMetadata::fetch(uri, FetchLimits::default()).await),
+        (true, None) => {
+            eprintln!(
+                "{} account {} has no metadata uri to fetch",
+                "warning:".yellow().bold(),
+                account.pubkey
+            );
+            None
+        }
+        (false, _) => None,
+    };
+
+    match output {
+        OutputFormat::Table => {
+            println!();
+            parsed_account.display(&account.pubkey);
+            if let Some(offchain_metadata) = &offchain_metadata {
+                offchain_metadata.display();
+            }
+            println!();
+        }
+        OutputFormat::Json => {
+            let mut view = parsed_account.to_view(&fetched_account, &account.pubkey);
+            view.offchain_metadata = offchain_metadata.as_ref().map(OffChainMetadata::to_view);
+            print_json(&view)
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches an account, returning [`ExplorerError::NotFound`] if it does not
+/// exist
+pub async fn fetch_account(client: &Client, key: &Pubkey) -> Result<Account> {
+    client
+        .get_account_with_commitment(key, client.commitment())
+        .await?
+        .value
+        .ok_or_else(|| ExplorerError::NotFound(format!("account {key} not found")))
 }
 
 async fn parse_account<'a>(
     account: &'a Account,
     key: &'a Pubkey,
     client: &Client,
-) -> ParsedAccount<'a> {
-    // First try parse system program
-    SystemAccount::parse(account, key, client)
-        .await
-        // Then try parse token account
-        .or(TokenProgramAccount::parse(account, client).await)
-        // Finally, fallback (infallible)
-        .or_else(|| Some(ParsedAccount::Other(account)))
-        .unwrap()
+) -> Result<ParsedAccount<'a>> {
+    // First try parse nonce account, the only system accounts with data
+    if let Some(parsed) = NonceAccount::parse(account)? {
+        return Ok(parsed);
+    }
+
+    // Then try parse system program
+    if let Some(parsed) = SystemAccount::parse(account, key, client).await? {
+        return Ok(parsed);
+    }
+
+    // Then try parse token account
+    if let Some(parsed) = TokenProgramAccount::parse(account, key, client).await? {
+        return Ok(parsed);
+    }
+
+    // Then try parse stake account
+    if let Some(parsed) = StakeAccount::parse(account, client).await? {
+        return Ok(parsed);
+    }
+
+    // Then try parse vote account
+    if let Some(parsed) = VoteAccount::parse(account)? {
+        return Ok(parsed);
+    }
+
+    // Then try parse upgradeable program account
+    if let Some(parsed) = UpgradeableProgramAccount::parse(account, key, client).await? {
+        return Ok(parsed);
+    }
+
+    // Then try parse address lookup table account
+    if let Some(parsed) = LookupTableAccount::parse(account)? {
+        return Ok(parsed);
+    }
+
+    // Then try parse sysvar account
+    if let Some(parsed) = SysvarAccount::parse(account, key)? {
+        return Ok(parsed);
+    }
+
+    // Finally, fallback (infallible)
+    Ok(ParsedAccount::Other(account))
+}
+
+/// Parses a batch of accounts, e.g. all accounts of a program, without rpc
+/// requests per account. Token accounts' mints are fetched once for the whole
+/// batch, and parsers that need other accounts (system, stake and upgradeable
+/// program accounts) are skipped, leaving those accounts undecoded.
+pub async fn parse_accounts<'a>(
+    accounts: &'a [(Pubkey, Account)],
+    client: &Client,
+) -> Result<Vec<ParsedAccount<'a>>> {
+    let prefetched =
+        PrefetchedMints::fetch(accounts.iter().map(|(key, account)| (key, account)), client)
+            .await?;
+
+    let parsed = accounts
+        .iter()
+        .map(|(key, account)| {
+            // A single undecodable account shouldn't fail the whole batch
+            parse_account_prefetched(account, key, &prefetched).unwrap_or_else(|e| {
+                eprintln!(
+                    "{} failed to decode account {key}: {e}",
+                    "warning:".yellow().bold()
+                );
+                ParsedAccount::Other(account)
+            })
+        })
+        .collect();
+    Ok(parsed)
+}
+
+fn parse_account_prefetched<'a>(
+    account: &'a Account,
+    key: &'a Pubkey,
+    prefetched: &PrefetchedMints,
+) -> Result<ParsedAccount<'a>> {
+    if let Some(parsed) = NonceAccount::parse(account)? {
+        return Ok(parsed);
+    }
+    if let Some(parsed) = TokenProgramAccount::parse_prefetched(account, key, prefetched)? {
+        return Ok(parsed);
+    }
+    if let Some(parsed) = VoteAccount::parse(account)? {
+        return Ok(parsed);
+    }
+    if let Some(parsed) = LookupTableAccount::parse(account)? {
+        return Ok(parsed);
+    }
+    if let Some(parsed) = SysvarAccount::parse(account, key)? {
+        return Ok(parsed);
+    }
+    Ok(ParsedAccount::Other(account))
 }
 
 pub enum ParsedAccount<'a> {
     System(SystemAccount<'a>),
-    TokenProgram(TokenProgramAccount),
+    TokenProgram(Box<TokenProgramAccount>),
+    Stake(Box<StakeAccount>),
+    Vote(Box<VoteAccount>),
+    UpgradeableProgram(Box<UpgradeableProgramAccount>),
+    LookupTable(Box<LookupTableAccount<'a>>),
+    Nonce(Box<NonceAccount>),
+    Sysvar(Box<SysvarAccount>),
     Other(&'a Account),
 }
 
 impl<'a> ParsedAccount<'a> {
+    /// The URI of the off-chain metadata for mints and token accounts
+    pub fn metadata_uri(&self) -> Option<&str> {
+        match self {
+            ParsedAccount::TokenProgram(token) => token.metadata_uri(),
+            _ => None,
+        }
+    }
+
     pub fn display(self, key: &Pubkey) {
         match self {
             ParsedAccount::System(system) => system.display(),
             ParsedAccount::TokenProgram(token) => token.display(key),
+            ParsedAccount::Stake(stake) => stake.display(key),
+            ParsedAccount::Vote(vote) => vote.display(key),
+            ParsedAccount::UpgradeableProgram(program) => program.display(key),
+            ParsedAccount::LookupTable(table) => table.display(key),
+            ParsedAccount::Nonce(nonce) => nonce.display(key),
+            ParsedAccount::Sysvar(sysvar) => sysvar.display(key),
             ParsedAccount::Other(other) => other_display(other, key),
         }
     }
+
+    pub fn to_view(&self, account: &Account, key: &Pubkey) -> AccountView {
+        let parsed = match self {
+            ParsedAccount::System(system) => ParsedAccountView::System {
+                token_accounts: system.token_accounts.clone(),
+            },
+            ParsedAccount::TokenProgram(token) => token.to_view(key),
+            ParsedAccount::Stake(stake) => ParsedAccountView::Stake(stake.to_view()),
+            ParsedAccount::Vote(vote) => ParsedAccountView::Vote(vote.to_view()),
+            ParsedAccount::UpgradeableProgram(program) => program.to_view(),
+            ParsedAccount::LookupTable(table) => ParsedAccountView::LookupTable(table.to_view()),
+            ParsedAccount::Nonce(nonce) => ParsedAccountView::Nonce(nonce.to_view()),
+            ParsedAccount::Sysvar(sysvar) => ParsedAccountView::Sysvar(sysvar.to_view()),
+            ParsedAccount::Other(other) => ParsedAccountView::Other {
+                data: base64::engine::general_purpose::STANDARD.encode(&other.data),
+            },
+        };
+
+        AccountView {
+            pubkey: key.to_string(),
+            owner: account.owner.to_string(),
+            lamports: account.lamports,
+            executable: account.executable,
+            data_len: account.data.len(),
+            parsed,
+            offchain_metadata: None,
+        }
+    }
+}
+
+/// Serializable view model of a [`ParsedAccount`]
+#[derive(Serialize)]
+pub struct AccountView {
+    pub pubkey: String,
+    pub owner: String,
+    pub lamports: u64,
+    pub executable: bool,
+    pub data_len: usize,
+    #[serde(flatten)]
+    pub parsed: ParsedAccountView,
+    /// Only fetched with `--fetch-uri`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offchain_metadata: Option<OffChainMetadataView>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParsedAccountView {
+    System {
+        token_accounts: Vec<TokenAccountBalance>,
+    },
+    TokenAccount(TokenAccountView),
+    Mint(MintAccountView),
+    TokenMultisig(MultisigAccountView),
+    Stake(StakeAccountView),
+    Vote(VoteAccountView),
+    Program(ProgramView),
+    ProgramData(ProgramDataView),
+    Buffer(BufferView),
+    NonUpgradeableProgram(NonUpgradeableProgramView),
+    LookupTable(LookupTableView),
+    Nonce(NonceAccountView),
+    Sysvar(SysvarView),
+    Other {
+        /// base64 encoded account data
+        data: String,
+    },
 }
 
 fn other_display(other: &Account, key: &Pubkey) {
@@ -101,7 +344,7 @@ fn other_display(other: &Account, key: &Pubkey) {
     tables.printstd();
 
     let mut stdout = std::io::stdout();
-    stdout.write(data_string.as_bytes()).unwrap();
+    stdout.write_all(data_string.as_bytes()).unwrap();
     stdout.flush().unwrap();
 }
 
@@ -112,20 +355,28 @@ impl<'a> ParsedAccount<'a> {
         token_account: spl_token::state::Account,
         mint_account: spl_token::state::Mint,
         symbol: Option<String>,
+        uri: Option<String>,
     ) -> ParsedAccount<'a> {
-        ParsedAccount::TokenProgram(TokenProgramAccount::Tokenkeg(
+        ParsedAccount::TokenProgram(Box::new(TokenProgramAccount::Tokenkeg(
             TokenkegAccount::TokenAccount {
                 token_account,
                 mint_account,
                 symbol,
+                uri,
             },
-        ))
+        )))
     }
 
     #[inline(always)]
-    pub fn tokenkeg_mint(mint: spl_token::state::Mint) -> ParsedAccount<'a> {
-        ParsedAccount::TokenProgram(TokenProgramAccount::Tokenkeg(TokenkegAccount::MintAccount(
-            mint,
+    pub fn tokenkeg_mint(
+        mint_account: spl_token::state::Mint,
+        metadata: Option<MetaplexMetadata>,
+    ) -> ParsedAccount<'a> {
+        ParsedAccount::TokenProgram(Box::new(TokenProgramAccount::Tokenkeg(
+            TokenkegAccount::MintAccount {
+                mint_account,
+                metadata,
+            },
         )))
     }
 
@@ -134,22 +385,32 @@ impl<'a> ParsedAccount<'a> {
         token_account: spl_token_2022::state::Account,
         mint_account: spl_token_2022::state::Mint,
         symbol: Option<String>,
+        uri: Option<String>,
+        extensions: Vec<TokenExtension>,
     ) -> ParsedAccount<'a> {
-        ParsedAccount::TokenProgram(TokenProgramAccount::Token22(Token22Account::TokenAccount {
-            token_account,
-            mint_account,
-            symbol,
-        }))
+        ParsedAccount::TokenProgram(Box::new(TokenProgramAccount::Token22(
+            Token22Account::TokenAccount {
+                token_account,
+                mint_account,
+                symbol,
+                uri,
+                extensions,
+            },
+        )))
     }
 
     #[inline(always)]
     pub fn token22_mint(
         mint_account: spl_token_2022::state::Mint,
-        extensions: Vec<ExtensionType>,
+        extensions: Vec<TokenExtension>,
+        metadata: Option<MetaplexMetadata>,
     ) -> ParsedAccount<'a> {
-        ParsedAccount::TokenProgram(TokenProgramAccount::Token22(Token22Account::MintAccount {
-            mint_account,
-            extensions,
-        }))
+        ParsedAccount::TokenProgram(Box::new(TokenProgramAccount::Token22(
+            Token22Account::MintAccount {
+                mint_account,
+                extensions,
+                metadata,
+            },
+        )))
     }
 }
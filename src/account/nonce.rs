@@ -0,0 +1,113 @@
+//! Parsing durable nonce accounts
+
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
+use solana_sdk::{
+    account::Account,
+    nonce::{
+        state::{Data, Versions},
+        State,
+    },
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{error::Result, utils::display_balance};
+
+use super::ParsedAccount;
+
+pub struct NonceAccount {
+    pub lamports: u64,
+    /// Legacy nonces store the raw blockhash, and must be advanced before
+    /// they can be used for durable transactions
+    pub legacy: bool,
+    /// `None` if the nonce account is not initialized
+    pub data: Option<Data>,
+}
+
+impl NonceAccount {
+    pub fn parse(account: &Account) -> Result<Option<ParsedAccount<'_>>> {
+        // Nonce accounts are the only system accounts with data
+        if account.owner != system_program::ID || account.data.len() != State::size() {
+            return Ok(None);
+        }
+
+        // Other 80-byte system accounts fall back to the system account view
+        let Ok(versions) = account.deserialize_data::<Versions>() else {
+            return Ok(None);
+        };
+
+        let legacy = matches!(versions, Versions::Legacy(_));
+        let data = match versions.state() {
+            State::Initialized(data) => Some(data.clone()),
+            State::Uninitialized => None,
+        };
+
+        Ok(Some(ParsedAccount::Nonce(Box::new(NonceAccount {
+            lamports: account.lamports,
+            legacy,
+            data,
+        }))))
+    }
+
+    pub fn display(self, key: &Pubkey) {
+        let mut nonce_table = Table::new();
+        nonce_table.set_titles(row![c->"Nonce Account", key]);
+        nonce_table.add_row(row![c->"SOL Balance", display_balance(self.lamports, 9)]);
+        match &self.data {
+            Some(data) => {
+                nonce_table.add_row(row![c->"State", self.state_name()]);
+                nonce_table.add_row(row![c->"Authority", data.authority]);
+                nonce_table.add_row(row![c->"Durable Nonce", data.blockhash()]);
+                nonce_table.add_row(row![
+                    c->"Fee Per Signature",
+                    display_balance(data.get_lamports_per_signature(), 9)
+                ]);
+            }
+            None => {
+                nonce_table.add_row(row![c->"State", "Uninitialized"]);
+            }
+        }
+
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+        let padded_width = width.saturating_sub(4);
+
+        let mut tables = Table::new();
+        tables.add_row(row![c->nonce_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+        tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+        tables.printstd();
+    }
+
+    fn state_name(&self) -> &'static str {
+        match (&self.data, self.legacy) {
+            (None, _) => "Uninitialized",
+            (Some(_), true) => "Initialized (legacy, advance before use)",
+            (Some(_), false) => "Initialized",
+        }
+    }
+
+    pub fn to_view(&self) -> NonceAccountView {
+        NonceAccountView {
+            initialized: self.data.is_some(),
+            legacy: self.legacy,
+            authority: self.data.as_ref().map(|data| data.authority.to_string()),
+            durable_nonce: self.data.as_ref().map(|data| data.blockhash().to_string()),
+            lamports_per_signature: self.data.as_ref().map(Data::get_lamports_per_signature),
+        }
+    }
+}
+
+/// Serializable view model of a [`NonceAccount`]
+#[derive(Serialize)]
+pub struct NonceAccountView {
+    pub initialized: bool,
+    /// Legacy nonces must be advanced before they can be used
+    pub legacy: bool,
+    pub authority: Option<String>,
+    /// The stored durable nonce, used as the transaction's recent blockhash
+    pub durable_nonce: Option<String>,
+    pub lamports_per_signature: Option<u64>,
+}
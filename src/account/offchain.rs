@@ -0,0 +1,377 @@
+//! Fetching the off-chain JSON metadata that a token's metadata URI points to
+
+use std::time::Duration;
+
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::insert_newlines;
+
+/// Bounds on fetching a URI, since it is arbitrary data controlled by the
+/// token's update authority
+#[derive(Debug, Clone, Copy)]
+pub struct FetchLimits {
+    /// Timeout for the whole request, including reading the body
+    pub timeout: Duration,
+    /// Maximum size of the response body, in bytes
+    pub max_size: usize,
+}
+
+impl Default for FetchLimits {
+    fn default() -> FetchLimits {
+        FetchLimits {
+            timeout: Duration::from_secs(10),
+            max_size: 1024 * 1024,
+        }
+    }
+}
+
+/// The JSON document described by the Metaplex token metadata standard. All
+/// fields are optional, as the JSON is not validated on-chain.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct OffChainJson {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub animation_url: Option<String>,
+    pub external_url: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub attributes: Vec<Attribute>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub properties: Properties,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Attribute {
+    pub trait_type: Option<String>,
+    /// Usually a string, but numbers are common too
+    #[serde(default)]
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Properties {
+    #[serde(default, deserialize_with = "lenient")]
+    pub files: Vec<File>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct File {
+    pub uri: Option<String>,
+    #[serde(rename = "type")]
+    pub mime_type: Option<String>,
+}
+
+/// Falls back to the default for malformed optional sections, rather than
+/// rejecting the whole document
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned + Default,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+/// The result of fetching a metadata URI. Failures are kept rather than
+/// returned as errors, since the on-chain account is still worth showing.
+pub struct OffChainMetadata {
+    pub uri: String,
+    pub json: std::result::Result<OffChainJson, String>,
+}
+
+impl OffChainMetadata {
+    pub async fn fetch(uri: &str, limits: FetchLimits) -> OffChainMetadata {
+        OffChainMetadata {
+            uri: uri.to_string(),
+            json: fetch_json(uri, limits).await,
+        }
+    }
+
+    pub fn display(&self) {
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+        let padded_width = width.saturating_sub(4);
+        // Leave room for the label column and borders
+        let value_width = width.saturating_sub(32).max(16);
+
+        let mut metadata_table = Table::new();
+        metadata_table
+            .set_titles(row![c->"Off-Chain Metadata", insert_newlines(&self.uri, value_width)]);
+        match &self.json {
+            Ok(json) => {
+                let fields = [
+                    ("Name", &json.name),
+                    ("Symbol", &json.symbol),
+                    ("Description", &json.description),
+                    ("Image", &json.image),
+                    ("Animation URL", &json.animation_url),
+                    ("External URL", &json.external_url),
+                ];
+                for (label, value) in fields {
+                    if let Some(value) = value {
+                        metadata_table.add_row(row![c->label, insert_newlines(value, value_width)]);
+                    }
+                }
+
+                if !json.attributes.is_empty() {
+                    let mut attributes_table = Table::new();
+                    attributes_table.set_titles(row![c->"Trait", c->"Value"]);
+                    for attribute in &json.attributes {
+                        attributes_table.add_row(row![
+                            attribute.trait_type.as_deref().unwrap_or(""),
+                            format_value(&attribute.value)
+                        ]);
+                    }
+                    metadata_table.add_row(row![c->"Attributes", attributes_table]);
+                }
+
+                if !json.properties.files.is_empty() {
+                    let mut files_table = Table::new();
+                    files_table.set_titles(row![c->"URI", c->"Type"]);
+                    for file in &json.properties.files {
+                        files_table.add_row(row![
+                            insert_newlines(file.uri.as_deref().unwrap_or(""), value_width),
+                            file.mime_type.as_deref().unwrap_or("")
+                        ]);
+                    }
+                    metadata_table.add_row(row![c->"Files", files_table]);
+                }
+            }
+            Err(error) => {
+                metadata_table.add_row(row![c->"Error", Fy->insert_newlines(error, value_width)]);
+            }
+        }
+
+        let mut tables = Table::new();
+        tables.add_row(row![c->metadata_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+        tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+        tables.printstd();
+    }
+
+    pub fn to_view(&self) -> OffChainMetadataView {
+        OffChainMetadataView {
+            uri: self.uri.clone(),
+            json: self.json.as_ref().ok().cloned(),
+            error: self.json.as_ref().err().cloned(),
+        }
+    }
+}
+
+/// Serializable view model of [`OffChainMetadata`]. Exactly one of `json` and
+/// `error` is set.
+#[derive(Serialize)]
+pub struct OffChainMetadataView {
+    pub uri: String,
+    pub json: Option<OffChainJson>,
+    pub error: Option<String>,
+}
+
+/// Fetches and decodes the JSON at `uri`, enforcing the timeout and size limit
+pub async fn fetch_json(
+    uri: &str,
+    limits: FetchLimits,
+) -> std::result::Result<OffChainJson, String> {
+    let url = gateway_url(uri);
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(format!("unsupported uri scheme: {uri}"));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(limits.timeout)
+        .build()
+        .map_err(|e| format!("failed to build http client: {e}"))?;
+    let mut response = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| format!("failed to fetch {url}: {e}"))?;
+
+    // Check the advertised length first, but also count the bytes actually
+    // read since it may be missing or wrong
+    let too_large = || format!("response exceeds {} bytes", limits.max_size);
+    if response
+        .content_length()
+        .is_some_and(|len| len > limits.max_size as u64)
+    {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("failed to read {url}: {e}"))?
+    {
+        if body.len() + chunk.len() > limits.max_size {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    serde_json::from_slice(&body).map_err(|e| format!("invalid metadata json: {e}"))
+}
+
+/// Rewrites content addressed URIs to public HTTP gateways
+fn gateway_url(uri: &str) -> String {
+    let uri = uri.trim();
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        format!("https://ipfs.io/ipfs/{}", path.trim_start_matches("ipfs/"))
+    } else if let Some(path) = uri.strip_prefix("ar://") {
+        format!("https://arweave.net/{path}")
+    } else {
+        uri.to_string()
+    }
+}
+
+/// Shows strings without quotes, and anything else as JSON
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        thread,
+        time::Instant,
+    };
+
+    use super::*;
+
+    /// Serves a single connection on a local port, passing the stream to
+    /// `respond` once the request has been read. Returns the url to fetch.
+    fn serve(respond: impl FnOnce(TcpStream) + Send + 'static) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/metadata.json", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    return;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            respond(stream);
+        });
+        url
+    }
+
+    fn respond_json(body: &'static str) -> impl FnOnce(TcpStream) + Send + 'static {
+        move |mut stream| {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+    }
+
+    fn limits(timeout: Duration, max_size: usize) -> FetchLimits {
+        FetchLimits { timeout, max_size }
+    }
+
+    #[tokio::test]
+    async fn fetches_valid_json() {
+        let url = serve(respond_json(
+            r#"{"name":"Token","symbol":"TKN","image":"https://example.com/t.png",
+                "attributes":[{"trait_type":"Level","value":3}],
+                "properties":{"files":[{"uri":"https://example.com/t.png","type":"image/png"}]}}"#,
+        ));
+
+        let json = fetch_json(&url, FetchLimits::default()).await.unwrap();
+        assert_eq!(json.name.as_deref(), Some("Token"));
+        assert_eq!(json.symbol.as_deref(), Some("TKN"));
+        assert_eq!(json.attributes.len(), 1);
+        assert_eq!(json.attributes[0].value, Value::from(3));
+        assert_eq!(json.properties.files.len(), 1);
+        assert_eq!(
+            json.properties.files[0].mime_type.as_deref(),
+            Some("image/png")
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_optional_sections_are_ignored() {
+        let url = serve(respond_json(
+            r#"{"name":"Token","attributes":"none","properties":{"files":5}}"#,
+        ));
+
+        let json = fetch_json(&url, FetchLimits::default()).await.unwrap();
+        assert_eq!(json.name.as_deref(), Some("Token"));
+        assert!(json.attributes.is_empty());
+        assert!(json.properties.files.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_body_without_reading_it_all() {
+        // A chunked body that exceeds the limit and then never ends, so the
+        // fetch only returns if it stops reading at the limit
+        let url = serve(|mut stream| {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n"
+            )
+            .unwrap();
+            let chunk = [b' '; 1024];
+            for _ in 0..4 {
+                write!(stream, "{:x}\r\n", chunk.len()).unwrap();
+                stream.write_all(&chunk).unwrap();
+                write!(stream, "\r\n").unwrap();
+            }
+            stream.flush().unwrap();
+            thread::sleep(Duration::from_secs(10));
+        });
+
+        let start = Instant::now();
+        let error = fetch_json(&url, limits(Duration::from_secs(5), 2048))
+            .await
+            .unwrap_err();
+        assert!(error.contains("exceeds 2048 bytes"), "{error}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_content_length() {
+        let url = serve(|mut stream| {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: 1000000\r\n\r\n{{"
+            )
+            .unwrap();
+            stream.flush().unwrap();
+            thread::sleep(Duration::from_secs(10));
+        });
+
+        let start = Instant::now();
+        let error = fetch_json(&url, limits(Duration::from_secs(5), 2048))
+            .await
+            .unwrap_err();
+        assert!(error.contains("exceeds 2048 bytes"), "{error}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn times_out_on_stalled_server() {
+        let url = serve(|_stream| thread::sleep(Duration::from_secs(10)));
+
+        let start = Instant::now();
+        let error = fetch_json(&url, limits(Duration::from_millis(200), 2048))
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("failed to fetch"), "{error}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
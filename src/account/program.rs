@@ -0,0 +1,400 @@
+//! Parsing bpf loader accounts (upgradeable programs, program data, buffers,
+//! and programs of the non-upgradeable loaders)
+
+use num_format::{Locale, ToFormattedString};
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient as Client;
+use solana_sdk::{
+    account::Account,
+    bpf_loader, bpf_loader_deprecated,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Slot,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::{ExplorerError, Result},
+    utils::display_balance,
+};
+
+use super::{
+    executable::{Executable, SecurityTxt},
+    ParsedAccount, ParsedAccountView,
+};
+
+pub enum UpgradeableProgramAccount {
+    Program {
+        lamports: u64,
+        programdata_address: Pubkey,
+        /// `None` if the program was closed
+        programdata: Option<ProgramData>,
+    },
+    ProgramData(ProgramData),
+    Buffer {
+        lamports: u64,
+        authority: Option<Pubkey>,
+        data_len: usize,
+        executable: Executable,
+    },
+    /// Program deployed with `bpf_loader` or `bpf_loader_deprecated`, whose
+    /// account data is the executable itself
+    NonUpgradeable {
+        lamports: u64,
+        loader: Pubkey,
+        data_len: usize,
+        executable: Executable,
+    },
+}
+
+pub struct ProgramData {
+    pub address: Pubkey,
+    pub lamports: u64,
+    /// Slot in which the program was last deployed
+    pub slot: Slot,
+    /// `None` if the program is immutable
+    pub upgrade_authority: Option<Pubkey>,
+    pub executable_len: usize,
+    pub executable: Executable,
+}
+
+impl ProgramData {
+    fn parse(address: Pubkey, account: &Account) -> Result<ProgramData> {
+        match deserialize_state(account)? {
+            UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address,
+            } => {
+                let elf = account
+                    .data
+                    .get(UpgradeableLoaderState::size_of_programdata_metadata()..)
+                    .unwrap_or_default();
+                Ok(ProgramData {
+                    address,
+                    lamports: account.lamports,
+                    slot,
+                    upgrade_authority: upgrade_authority_address,
+                    executable_len: elf.len(),
+                    executable: Executable::parse(elf),
+                })
+            }
+            _ => Err(ExplorerError::Decode(format!(
+                "program data {address}: not a program data account"
+            ))),
+        }
+    }
+}
+
+impl UpgradeableProgramAccount {
+    pub async fn parse<'a>(
+        account: &'a Account,
+        key: &Pubkey,
+        client: &Client,
+    ) -> Result<Option<ParsedAccount<'a>>> {
+        if account.executable
+            && [bpf_loader::ID, bpf_loader_deprecated::ID].contains(&account.owner)
+        {
+            return Ok(Some(ParsedAccount::UpgradeableProgram(Box::new(
+                UpgradeableProgramAccount::NonUpgradeable {
+                    lamports: account.lamports,
+                    loader: account.owner,
+                    data_len: account.data.len(),
+                    executable: Executable::parse(&account.data),
+                },
+            ))));
+        }
+        if account.owner != bpf_loader_upgradeable::ID {
+            return Ok(None);
+        }
+
+        let parsed = match deserialize_state(account)? {
+            UpgradeableLoaderState::Program {
+                programdata_address,
+            } => {
+                // Follow the link to the program data account
+                let programdata = client
+                    .get_account_with_commitment(&programdata_address, client.commitment())
+                    .await?
+                    .value
+                    .map(|programdata| ProgramData::parse(programdata_address, &programdata))
+                    .transpose()?;
+
+                UpgradeableProgramAccount::Program {
+                    lamports: account.lamports,
+                    programdata_address,
+                    programdata,
+                }
+            }
+            UpgradeableLoaderState::ProgramData { .. } => {
+                UpgradeableProgramAccount::ProgramData(ProgramData::parse(*key, account)?)
+            }
+            UpgradeableLoaderState::Buffer { authority_address } => {
+                let elf = account
+                    .data
+                    .get(UpgradeableLoaderState::size_of_buffer_metadata()..)
+                    .unwrap_or_default();
+                UpgradeableProgramAccount::Buffer {
+                    lamports: account.lamports,
+                    authority: authority_address,
+                    data_len: elf.len(),
+                    executable: Executable::parse(elf),
+                }
+            }
+            // Nothing to show beyond the raw account
+            UpgradeableLoaderState::Uninitialized => return Ok(None),
+        };
+
+        Ok(Some(ParsedAccount::UpgradeableProgram(Box::new(parsed))))
+    }
+
+    pub fn display(self, key: &Pubkey) {
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+        let padded_width = width.saturating_sub(4);
+
+        let mut program_table = Table::new();
+        let executable = match &self {
+            UpgradeableProgramAccount::Program {
+                lamports,
+                programdata_address,
+                programdata,
+            } => {
+                program_table.set_titles(row![c->"Program", key]);
+                program_table.add_row(row![c->"SOL Balance", display_balance(*lamports, 9)]);
+                program_table.add_row(row![c->"ProgramData Address", programdata_address]);
+                match programdata {
+                    Some(programdata) => {
+                        add_programdata_rows(&mut program_table, programdata);
+                        Some(&programdata.executable)
+                    }
+                    None => {
+                        program_table.add_row(row![c->"Status", "Closed"]);
+                        None
+                    }
+                }
+            }
+            UpgradeableProgramAccount::ProgramData(programdata) => {
+                program_table.set_titles(row![c->"Program Data", key]);
+                program_table
+                    .add_row(row![c->"SOL Balance", display_balance(programdata.lamports, 9)]);
+                add_programdata_rows(&mut program_table, programdata);
+                Some(&programdata.executable)
+            }
+            UpgradeableProgramAccount::Buffer {
+                lamports,
+                authority,
+                data_len,
+                executable,
+            } => {
+                program_table.set_titles(row![c->"Program Buffer", key]);
+                program_table.add_row(row![c->"SOL Balance", display_balance(*lamports, 9)]);
+                program_table.add_row(row![c->"Authority", format_authority(authority)]);
+                program_table.add_row(row![c->"Data Length", format_len(*data_len)]);
+                program_table.add_row(row![c->"SHA-256", executable.hash]);
+                Some(executable)
+            }
+            UpgradeableProgramAccount::NonUpgradeable {
+                lamports,
+                loader,
+                data_len,
+                executable,
+            } => {
+                program_table.set_titles(row![c->"Program", key]);
+                program_table.add_row(row![c->"SOL Balance", display_balance(*lamports, 9)]);
+                program_table.add_row(row![c->"Loader", loader]);
+                program_table.add_row(row![c->"Upgrade Authority", "None (not upgradeable)"]);
+                program_table.add_row(row![c->"Executable Size", format_len(*data_len)]);
+                program_table.add_row(row![c->"SHA-256", executable.hash]);
+                Some(executable)
+            }
+        };
+
+        let mut tables = Table::new();
+        tables.add_row(row![c->program_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+        if let Some(security_txt) =
+            executable.and_then(|executable| executable.security_txt.as_ref())
+        {
+            // Leave room for the labels and borders
+            tables.add_row(row![c->security_txt.table(width.saturating_sub(32))]);
+            tables.add_row(row![" ".repeat(padded_width)]);
+        }
+        tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+        tables.printstd();
+    }
+
+    pub fn to_view(&self) -> ParsedAccountView {
+        match self {
+            UpgradeableProgramAccount::Program {
+                programdata_address,
+                programdata,
+                ..
+            } => ParsedAccountView::Program(ProgramView {
+                programdata_address: programdata_address.to_string(),
+                closed: programdata.is_none(),
+                programdata: programdata.as_ref().map(ProgramData::to_view),
+            }),
+            UpgradeableProgramAccount::ProgramData(programdata) => {
+                ParsedAccountView::ProgramData(programdata.to_view())
+            }
+            UpgradeableProgramAccount::Buffer {
+                authority,
+                data_len,
+                executable,
+                ..
+            } => ParsedAccountView::Buffer(BufferView {
+                authority: authority.as_ref().map(ToString::to_string),
+                data_len: *data_len,
+                sha256: executable.hash.clone(),
+                security_txt: executable.security_txt.clone(),
+            }),
+            UpgradeableProgramAccount::NonUpgradeable {
+                loader,
+                data_len,
+                executable,
+                ..
+            } => ParsedAccountView::NonUpgradeableProgram(NonUpgradeableProgramView {
+                loader: loader.to_string(),
+                executable_len: *data_len,
+                sha256: executable.hash.clone(),
+                security_txt: executable.security_txt.clone(),
+            }),
+        }
+    }
+}
+
+impl ProgramData {
+    fn to_view(&self) -> ProgramDataView {
+        ProgramDataView {
+            address: self.address.to_string(),
+            last_deployed_slot: self.slot,
+            upgrade_authority: self.upgrade_authority.as_ref().map(ToString::to_string),
+            executable_len: self.executable_len,
+            sha256: self.executable.hash.clone(),
+            security_txt: self.executable.security_txt.clone(),
+        }
+    }
+}
+
+/// Serializable view model of an upgradeable program account
+#[derive(Serialize)]
+pub struct ProgramView {
+    pub programdata_address: String,
+    pub closed: bool,
+    pub programdata: Option<ProgramDataView>,
+}
+
+/// Serializable view model of a [`ProgramData`] account. A `null` upgrade
+/// authority means the program is immutable.
+#[derive(Serialize)]
+pub struct ProgramDataView {
+    pub address: String,
+    pub last_deployed_slot: Slot,
+    pub upgrade_authority: Option<String>,
+    pub executable_len: usize,
+    /// SHA-256 of the executable without trailing zeros, as reported by
+    /// verified builds
+    pub sha256: String,
+    pub security_txt: Option<SecurityTxt>,
+}
+
+/// Serializable view model of a program buffer account
+#[derive(Serialize)]
+pub struct BufferView {
+    pub authority: Option<String>,
+    pub data_len: usize,
+    pub sha256: String,
+    pub security_txt: Option<SecurityTxt>,
+}
+
+/// Serializable view model of a program deployed with one of the
+/// non-upgradeable loaders
+#[derive(Serialize)]
+pub struct NonUpgradeableProgramView {
+    pub loader: String,
+    pub executable_len: usize,
+    pub sha256: String,
+    pub security_txt: Option<SecurityTxt>,
+}
+
+/// The executable of a program, program data or buffer account with the
+/// loader header stripped. Program accounts are resolved to their program
+/// data account. Accounts of other programs are returned as is.
+pub async fn dump_data(key: &Pubkey, account: Account, client: &Client) -> Result<Vec<u8>> {
+    if account.owner != bpf_loader_upgradeable::ID {
+        return Ok(account.data);
+    }
+
+    let (account, metadata_len) = match deserialize_state(&account)? {
+        UpgradeableLoaderState::Program {
+            programdata_address,
+        } => {
+            let programdata = client
+                .get_account_with_commitment(&programdata_address, client.commitment())
+                .await?
+                .value
+                .ok_or_else(|| {
+                    ExplorerError::NotFound(format!(
+                        "program data {programdata_address} of program {key} not found (the program may be closed)"
+                    ))
+                })?;
+            (
+                programdata,
+                UpgradeableLoaderState::size_of_programdata_metadata(),
+            )
+        }
+        UpgradeableLoaderState::ProgramData { .. } => (
+            account,
+            UpgradeableLoaderState::size_of_programdata_metadata(),
+        ),
+        UpgradeableLoaderState::Buffer { .. } => {
+            (account, UpgradeableLoaderState::size_of_buffer_metadata())
+        }
+        UpgradeableLoaderState::Uninitialized => (account, 0),
+    };
+
+    Ok(account
+        .data
+        .get(metadata_len..)
+        .unwrap_or_default()
+        .to_vec())
+}
+
+/// Whether the account is owned by one of the bpf loaders
+pub fn is_program_account(account: &Account) -> bool {
+    [
+        bpf_loader_upgradeable::ID,
+        bpf_loader::ID,
+        bpf_loader_deprecated::ID,
+    ]
+    .contains(&account.owner)
+}
+
+fn deserialize_state(account: &Account) -> Result<UpgradeableLoaderState> {
+    // Only the metadata prefix is deserialized, the executable follows it
+    account
+        .deserialize_data()
+        .map_err(|e| ExplorerError::Decode(format!("upgradeable loader account: {e}")))
+}
+
+fn add_programdata_rows(table: &mut Table, programdata: &ProgramData) {
+    table.add_row(row![
+        c->"Upgrade Authority",
+        format_authority(&programdata.upgrade_authority)
+    ]);
+    table.add_row(row![c->"Last Deployed Slot", programdata.slot]);
+    table.add_row(row![c->"Executable Size", format_len(programdata.executable_len)]);
+    table.add_row(row![c->"SHA-256", programdata.executable.hash]);
+}
+
+fn format_authority(authority: &Option<Pubkey>) -> String {
+    match authority {
+        Some(authority) => authority.to_string(),
+        None => "None (immutable)".to_string(),
+    }
+}
+
+fn format_len(len: usize) -> String {
+    format!("{} bytes", len.to_formatted_string(&Locale::en))
+}
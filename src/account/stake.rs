@@ -0,0 +1,241 @@
+//! Parsing stake accounts
+
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient as Client;
+use solana_sdk::{
+    account::{from_account, Account},
+    clock::Epoch,
+    feature::{self, Feature},
+    feature_set::reduce_stake_warmup_cooldown,
+    pubkey::Pubkey,
+    stake::{
+        self,
+        state::{Lockup, StakeActivationStatus, StakeStateV2},
+    },
+    stake_history::StakeHistory,
+    sysvar,
+};
+
+use crate::{
+    error::{ExplorerError, Result},
+    utils::display_balance,
+};
+
+use super::ParsedAccount;
+
+pub struct StakeAccount {
+    pub lamports: u64,
+    pub state: StakeStateV2,
+    /// Effective, activating and deactivating stake as of `epoch`. Only
+    /// present for delegated stake accounts.
+    pub activation: Option<StakeActivationStatus>,
+    pub epoch: Epoch,
+}
+
+impl StakeAccount {
+    pub async fn parse<'a>(
+        account: &'a Account,
+        client: &Client,
+    ) -> Result<Option<ParsedAccount<'a>>> {
+        if account.owner != stake::program::ID {
+            return Ok(None);
+        }
+
+        let state: StakeStateV2 = account
+            .deserialize_data()
+            .map_err(|e| ExplorerError::Decode(format!("stake account: {e}")))?;
+
+        let epoch = client.get_epoch_info().await?.epoch;
+
+        // Compute the current activation status from the stake history
+        let activation = match &state {
+            StakeStateV2::Stake(_, stake, _) => {
+                let stake_history_account = client.get_account(&sysvar::stake_history::ID).await?;
+                let stake_history: StakeHistory = from_account(&stake_history_account)
+                    .ok_or_else(|| ExplorerError::Decode("stake history sysvar".to_string()))?;
+                let new_rate_activation_epoch = new_rate_activation_epoch(client).await?;
+
+                Some(stake.delegation.stake_activating_and_deactivating(
+                    epoch,
+                    &stake_history,
+                    new_rate_activation_epoch,
+                ))
+            }
+            _ => None,
+        };
+
+        Ok(Some(ParsedAccount::Stake(Box::new(StakeAccount {
+            lamports: account.lamports,
+            state,
+            activation,
+            epoch,
+        }))))
+    }
+
+    pub fn display(self, key: &Pubkey) {
+        let mut stake_table = Table::new();
+        stake_table.set_titles(row![c->"Stake Account", key]);
+        stake_table.add_row(row![c->"State", state_name(&self.state)]);
+        stake_table.add_row(row![c->"SOL Balance", display_balance(self.lamports, 9)]);
+
+        if let Some(meta) = self.state.meta() {
+            stake_table.add_row(row![
+                c->"Rent Exempt Reserve",
+                display_balance(meta.rent_exempt_reserve, 9)
+            ]);
+            stake_table.add_row(row![c->"Staker", meta.authorized.staker]);
+            stake_table.add_row(row![c->"Withdrawer", meta.authorized.withdrawer]);
+
+            let lockup = &meta.lockup;
+            if *lockup != Lockup::default() {
+                stake_table.add_row(row![c->"Lockup Epoch", lockup.epoch]);
+                stake_table.add_row(row![c->"Lockup Timestamp", lockup.unix_timestamp]);
+                stake_table.add_row(row![c->"Lockup Custodian", lockup.custodian]);
+            } else {
+                stake_table.add_row(row![c->"Lockup", "None"]);
+            }
+        }
+
+        if let Some(stake) = self.state.stake() {
+            let delegation = &stake.delegation;
+            stake_table.add_row(row![c->"Vote Account", delegation.voter_pubkey]);
+            stake_table.add_row(row![c->"Delegated Stake", display_balance(delegation.stake, 9)]);
+            stake_table
+                .add_row(row![c->"Activation Epoch", format_epoch(delegation.activation_epoch)]);
+            stake_table.add_row(row![
+                c->"Deactivation Epoch",
+                format_epoch(delegation.deactivation_epoch)
+            ]);
+            stake_table.add_row(row![c->"Credits Observed", stake.credits_observed]);
+        }
+
+        if let Some(activation) = &self.activation {
+            stake_table.add_row(row![c->"Current Epoch", self.epoch]);
+            stake_table.add_row(row![c->"Active Stake", display_balance(activation.effective, 9)]);
+            stake_table
+                .add_row(row![c->"Activating Stake", display_balance(activation.activating, 9)]);
+            stake_table.add_row(row![
+                c->"Deactivating Stake",
+                display_balance(activation.deactivating, 9)
+            ]);
+        }
+
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+        let padded_width = width.saturating_sub(4);
+
+        let mut tables = Table::new();
+        tables.add_row(row![c->stake_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+        tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+        tables.printstd();
+    }
+
+    pub fn to_view(&self) -> StakeAccountView {
+        let meta = self.state.meta();
+        let stake = self.state.stake();
+
+        StakeAccountView {
+            state: state_name(&self.state),
+            rent_exempt_reserve: meta.map(|meta| meta.rent_exempt_reserve),
+            staker: meta.map(|meta| meta.authorized.staker.to_string()),
+            withdrawer: meta.map(|meta| meta.authorized.withdrawer.to_string()),
+            lockup: meta.map(|meta| LockupView {
+                unix_timestamp: meta.lockup.unix_timestamp,
+                epoch: meta.lockup.epoch,
+                custodian: meta.lockup.custodian.to_string(),
+            }),
+            delegation: stake.map(|stake| DelegationView {
+                vote_account: stake.delegation.voter_pubkey.to_string(),
+                stake: stake.delegation.stake,
+                activation_epoch: stake.delegation.activation_epoch,
+                deactivation_epoch: stake.delegation.deactivation_epoch,
+                credits_observed: stake.credits_observed,
+            }),
+            epoch: self.epoch,
+            active_stake: self
+                .activation
+                .as_ref()
+                .map(|activation| activation.effective),
+            activating_stake: self
+                .activation
+                .as_ref()
+                .map(|activation| activation.activating),
+            deactivating_stake: self
+                .activation
+                .as_ref()
+                .map(|activation| activation.deactivating),
+        }
+    }
+}
+
+/// Serializable view model of a [`StakeAccount`]. Amounts are in lamports.
+#[derive(Serialize)]
+pub struct StakeAccountView {
+    pub state: &'static str,
+    pub rent_exempt_reserve: Option<u64>,
+    pub staker: Option<String>,
+    pub withdrawer: Option<String>,
+    pub lockup: Option<LockupView>,
+    pub delegation: Option<DelegationView>,
+    /// Epoch at which the activation amounts were computed
+    pub epoch: Epoch,
+    pub active_stake: Option<u64>,
+    pub activating_stake: Option<u64>,
+    pub deactivating_stake: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct LockupView {
+    pub unix_timestamp: i64,
+    pub epoch: Epoch,
+    pub custodian: String,
+}
+
+#[derive(Serialize)]
+pub struct DelegationView {
+    pub vote_account: String,
+    pub stake: u64,
+    pub activation_epoch: Epoch,
+    pub deactivation_epoch: Epoch,
+    pub credits_observed: u64,
+}
+
+fn state_name(state: &StakeStateV2) -> &'static str {
+    match state {
+        StakeStateV2::Uninitialized => "Uninitialized",
+        StakeStateV2::Initialized(_) => "Initialized",
+        StakeStateV2::Stake(..) => "Delegated",
+        StakeStateV2::RewardsPool => "Rewards Pool",
+    }
+}
+
+/// `Epoch::MAX` marks bootstrap stakes (activation) or active stakes
+/// (deactivation)
+fn format_epoch(epoch: Epoch) -> String {
+    if epoch == Epoch::MAX {
+        "None".to_string()
+    } else {
+        epoch.to_string()
+    }
+}
+
+/// The epoch at which the reduced (9%) stake warmup/cooldown rate took
+/// effect, if the feature is active on this cluster
+async fn new_rate_activation_epoch(client: &Client) -> Result<Option<Epoch>> {
+    let feature_account = client
+        .get_account_with_commitment(&reduce_stake_warmup_cooldown::id(), client.commitment())
+        .await?
+        .value;
+    let activated_at = feature_account
+        .as_ref()
+        .and_then(feature::from_account)
+        .and_then(|Feature { activated_at }| activated_at);
+
+    match activated_at {
+        Some(slot) => Ok(Some(client.get_epoch_schedule().await?.get_epoch(slot))),
+        None => Ok(None),
+    }
+}
@@ -11,7 +11,10 @@ use solana_sdk::{account::Account, pubkey::Pubkey, system_program};
 use spl_token_2022::extension::BaseStateWithExtensions;
 use spl_type_length_value::variable_len_pack::VariableLenPack;
 
-use crate::utils::display_balance;
+use crate::{
+    error::{ExplorerError, Result},
+    utils::display_balance,
+};
 
 use super::{token::TokenAccountBalance, ParsedAccount};
 
@@ -26,19 +29,20 @@ impl<'a> SystemAccount<'a> {
         account: &'a Account,
         key: &'a Pubkey,
         client: &Client,
-    ) -> Option<ParsedAccount<'a>> {
+    ) -> Result<Option<ParsedAccount<'a>>> {
         if account.owner != system_program::ID {
-            return None;
+            return Ok(None);
         }
 
         // Check if this account has tokenkeg accounts
         let tokenkeg_accounts_futures = client
             .get_token_accounts_by_owner(key, TokenAccountsFilter::ProgramId(spl_token::ID))
-            .await
-            .unwrap()
+            .await?
             .into_iter()
             .map(parse_keyed_account_to_token)
-            .map(|account| async move { get_symbol_for_token_account(&account, &client).await });
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .map(|account| async move { get_symbol_for_token_account(&account, client).await });
 
         let mut token_accounts: Vec<TokenAccountBalance> =
             futures::stream::iter(tokenkeg_accounts_futures)
@@ -49,11 +53,12 @@ impl<'a> SystemAccount<'a> {
         // Check if this account has token22 accounts
         let token22_accounts_futures = client
             .get_token_accounts_by_owner(key, TokenAccountsFilter::ProgramId(spl_token_2022::ID))
-            .await
-            .unwrap()
+            .await?
             .into_iter()
             .map(parse_keyed_account_to_token)
-            .map(|account| async move { get_symbol_for_token_account(&account, &client).await });
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .map(|account| async move { get_symbol_for_token_account(&account, client).await });
 
         // Collect all accounts
         token_accounts.extend(
@@ -71,11 +76,11 @@ impl<'a> SystemAccount<'a> {
             (None, None) => Ordering::Equal,
         });
 
-        Some(ParsedAccount::System(SystemAccount {
+        Ok(Some(ParsedAccount::System(SystemAccount {
             account,
             key,
             token_accounts,
-        }))
+        })))
     }
 
     pub fn display(self) {
@@ -112,14 +117,17 @@ impl<'a> SystemAccount<'a> {
     }
 }
 
-// RPC should have validated so this should be infallible
-fn parse_keyed_account_to_token(keyed_account: RpcKeyedAccount) -> TokenAccountBalance {
+// RPC should have validated, so this only fails on a misbehaving rpc
+fn parse_keyed_account_to_token(keyed_account: RpcKeyedAccount) -> Result<TokenAccountBalance> {
     // Get account data
     match keyed_account.account.data {
         UiAccountData::Json(json) => {
             TokenAccountBalance::parse_validated_json(json, keyed_account.pubkey)
         }
-        _ => unimplemented!("unused right now"),
+        _ => Err(ExplorerError::Decode(format!(
+            "token account {}: expected jsonParsed data",
+            keyed_account.pubkey
+        ))),
     }
 }
 
@@ -128,14 +136,21 @@ async fn get_symbol_for_token_account(
     account: &TokenAccountBalance,
     client: &Client,
 ) -> TokenAccountBalance {
-    let meta_or_mint: String = account.mint.to_string();
-
-    let mint_acc_key: Pubkey = match Pubkey::from_str(&meta_or_mint) {
+    let mint_acc_key: Pubkey = match Pubkey::from_str(&account.mint) {
         Ok(key) => key,
         Err(_) => return account.clone(), // or handle the error appropriately
     };
 
-    let mpl_metadata_key = mpl_token_metadata::accounts::Metadata::find_pda(&mint_acc_key).0;
+    TokenAccountBalance {
+        symbol: get_symbol_for_mint(&mint_acc_key, client).await,
+        ..account.clone()
+    }
+}
+
+/// Fetches the symbol of a mint, first from its metaplex metadata and then
+/// from its token-2022 metadata extension.
+pub async fn get_symbol_for_mint(mint: &Pubkey, client: &Client) -> Option<String> {
+    let mpl_metadata_key = mpl_token_metadata::accounts::Metadata::find_pda(mint).0;
 
     let mut symbol = client
         .get_account_data(&mpl_metadata_key)
@@ -144,28 +159,27 @@ async fn get_symbol_for_token_account(
         .and_then(|data| {
             let metadata = mpl_token_metadata::accounts::Metadata::from_bytes(&data);
 
-            metadata.map(|meta| meta.symbol).ok()
+            metadata
+                .map(|meta| meta.symbol.trim_end_matches('\0').to_string())
+                .ok()
         });
 
     if symbol.is_none() {
         use spl_token_metadata_interface::state::TokenMetadata;
-        if let Ok(mint_account) = client.get_account(&mint_acc_key).await {
-            let mint = spl_token_2022::extension::StateWithExtensions::<
+        if let Ok(mint_account) = client.get_account(mint).await {
+            if let Ok(mint) = spl_token_2022::extension::StateWithExtensions::<
                 spl_token_2022::state::Mint,
             >::unpack(&mint_account.data)
-            .unwrap();
-
-            if let Ok(token_metadata) = mint
-                .get_extension_bytes::<TokenMetadata>()
-                .and_then(<TokenMetadata as VariableLenPack>::unpack_from_slice)
             {
-                symbol.replace(token_metadata.symbol);
+                if let Ok(token_metadata) = mint
+                    .get_extension_bytes::<TokenMetadata>()
+                    .and_then(<TokenMetadata as VariableLenPack>::unpack_from_slice)
+                {
+                    symbol.replace(token_metadata.symbol);
+                }
             }
         }
     }
 
-    TokenAccountBalance {
-        symbol,
-        ..account.clone()
-    }
+    symbol
 }
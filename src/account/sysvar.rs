@@ -0,0 +1,357 @@
+//! Decoding sysvar accounts
+
+#![allow(deprecated)] // The Fees and RecentBlockhashes sysvars are deprecated
+
+use num_format::{Locale, ToFormattedString};
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
+use solana_sdk::{
+    account::{from_account, Account},
+    clock::{Clock, Epoch, Slot},
+    epoch_rewards::EpochRewards,
+    epoch_schedule::EpochSchedule,
+    pubkey::Pubkey,
+    rent::Rent,
+    slot_hashes::SlotHashes,
+    stake_history::StakeHistory,
+    sysvar::{
+        self, fees::Fees, last_restart_slot::LastRestartSlot,
+        recent_blockhashes::RecentBlockhashes, Sysvar,
+    },
+};
+
+use crate::{
+    error::{ExplorerError, Result},
+    utils::display_balance,
+};
+
+use super::ParsedAccount;
+
+pub enum SysvarAccount {
+    Clock(Clock),
+    Rent(Rent),
+    EpochSchedule(EpochSchedule),
+    EpochRewards(EpochRewards),
+    SlotHashes(SlotHashes),
+    StakeHistory(StakeHistory),
+    RecentBlockhashes(RecentBlockhashes),
+    Fees(Fees),
+    /// Only has data while a transaction is executing
+    Instructions,
+    LastRestartSlot(LastRestartSlot),
+}
+
+impl SysvarAccount {
+    pub fn parse<'a>(account: &Account, key: &Pubkey) -> Result<Option<ParsedAccount<'a>>> {
+        if account.owner != sysvar::ID {
+            return Ok(None);
+        }
+
+        let sysvar = match *key {
+            sysvar::clock::ID => SysvarAccount::Clock(decode(account, "clock")?),
+            sysvar::rent::ID => SysvarAccount::Rent(decode(account, "rent")?),
+            sysvar::epoch_schedule::ID => {
+                SysvarAccount::EpochSchedule(decode(account, "epoch schedule")?)
+            }
+            sysvar::epoch_rewards::ID => {
+                SysvarAccount::EpochRewards(decode(account, "epoch rewards")?)
+            }
+            sysvar::slot_hashes::ID => SysvarAccount::SlotHashes(decode(account, "slot hashes")?),
+            sysvar::stake_history::ID => {
+                SysvarAccount::StakeHistory(decode(account, "stake history")?)
+            }
+            // Deprecated sysvar ids are statics, not constants
+            key if key == sysvar::recent_blockhashes::ID => {
+                SysvarAccount::RecentBlockhashes(decode(account, "recent blockhashes")?)
+            }
+            key if key == sysvar::fees::ID => SysvarAccount::Fees(decode(account, "fees")?),
+            sysvar::instructions::ID => SysvarAccount::Instructions,
+            sysvar::last_restart_slot::ID => {
+                SysvarAccount::LastRestartSlot(decode(account, "last restart slot")?)
+            }
+            // e.g. SlotHistory, which is a large bitvec
+            _ => return Ok(None),
+        };
+
+        Ok(Some(ParsedAccount::Sysvar(Box::new(sysvar))))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SysvarAccount::Clock(_) => "Clock",
+            SysvarAccount::Rent(_) => "Rent",
+            SysvarAccount::EpochSchedule(_) => "Epoch Schedule",
+            SysvarAccount::EpochRewards(_) => "Epoch Rewards",
+            SysvarAccount::SlotHashes(_) => "Slot Hashes",
+            SysvarAccount::StakeHistory(_) => "Stake History",
+            SysvarAccount::RecentBlockhashes(_) => "Recent Blockhashes",
+            SysvarAccount::Fees(_) => "Fees",
+            SysvarAccount::Instructions => "Instructions",
+            SysvarAccount::LastRestartSlot(_) => "Last Restart Slot",
+        }
+    }
+
+    pub fn display(self, key: &Pubkey) {
+        let mut sysvar_table = Table::new();
+        sysvar_table.set_titles(row![c->format!("Sysvar: {}", self.name()), key]);
+
+        // Sysvars holding lists are shown in a second table
+        let mut list_table = None;
+        match &self {
+            SysvarAccount::Clock(clock) => {
+                sysvar_table.add_row(row![c->"Slot", clock.slot]);
+                sysvar_table.add_row(row![c->"Epoch", clock.epoch]);
+                sysvar_table.add_row(row![c->"Leader Schedule Epoch", clock.leader_schedule_epoch]);
+                sysvar_table.add_row(row![c->"Epoch Start Timestamp", clock.epoch_start_timestamp]);
+                sysvar_table.add_row(row![c->"Unix Timestamp", clock.unix_timestamp]);
+            }
+            SysvarAccount::Rent(rent) => {
+                sysvar_table
+                    .add_row(row![c->"Lamports Per Byte Year", rent.lamports_per_byte_year]);
+                sysvar_table.add_row(row![
+                    c->"Exemption Threshold",
+                    format!("{} years", rent.exemption_threshold)
+                ]);
+                sysvar_table.add_row(row![c->"Burn Percent", format!("{}%", rent.burn_percent)]);
+            }
+            SysvarAccount::EpochSchedule(schedule) => {
+                sysvar_table.add_row(row![c->"Slots Per Epoch", schedule.slots_per_epoch]);
+                sysvar_table.add_row(row![
+                    c->"Leader Schedule Slot Offset",
+                    schedule.leader_schedule_slot_offset
+                ]);
+                sysvar_table.add_row(row![c->"Warmup", schedule.warmup]);
+                sysvar_table.add_row(row![c->"First Normal Epoch", schedule.first_normal_epoch]);
+                sysvar_table.add_row(row![c->"First Normal Slot", schedule.first_normal_slot]);
+            }
+            SysvarAccount::EpochRewards(rewards) => {
+                sysvar_table.add_row(row![
+                    c->"Total Rewards",
+                    display_balance(rewards.total_rewards, 9)
+                ]);
+                sysvar_table.add_row(row![
+                    c->"Distributed Rewards",
+                    display_balance(rewards.distributed_rewards, 9)
+                ]);
+                sysvar_table.add_row(row![
+                    c->"Distribution Complete Block Height",
+                    rewards.distribution_complete_block_height
+                ]);
+            }
+            SysvarAccount::SlotHashes(slot_hashes) => {
+                sysvar_table.add_row(row![c->"Entries", slot_hashes.len()]);
+                let mut table = Table::new();
+                table.set_titles(row![c->"Slot", c->"Bank Hash"]);
+                for (slot, hash) in slot_hashes.iter() {
+                    table.add_row(row![r->slot, hash]);
+                }
+                list_table = Some(table);
+            }
+            SysvarAccount::StakeHistory(stake_history) => {
+                sysvar_table.add_row(row![c->"Entries", stake_history.len()]);
+                let mut table = Table::new();
+                table.set_titles(
+                    row![c->"Epoch", c->"Effective", c->"Activating", c->"Deactivating"],
+                );
+                for (epoch, entry) in stake_history.iter() {
+                    table.add_row(row![
+                        r->epoch,
+                        r->format_sol(entry.effective),
+                        r->format_sol(entry.activating),
+                        r->format_sol(entry.deactivating)
+                    ]);
+                }
+                list_table = Some(table);
+            }
+            SysvarAccount::RecentBlockhashes(recent_blockhashes) => {
+                sysvar_table.add_row(row![c->"Entries", recent_blockhashes.len()]);
+                let mut table = Table::new();
+                table.set_titles(row![c->"Blockhash", c->"Fee Per Signature"]);
+                for entry in recent_blockhashes.iter() {
+                    table.add_row(row![
+                        entry.blockhash,
+                        r->display_balance(entry.fee_calculator.lamports_per_signature, 9)
+                    ]);
+                }
+                list_table = Some(table);
+            }
+            SysvarAccount::Fees(fees) => {
+                sysvar_table.add_row(row![
+                    c->"Fee Per Signature",
+                    display_balance(fees.fee_calculator.lamports_per_signature, 9)
+                ]);
+            }
+            SysvarAccount::Instructions => {
+                sysvar_table.add_row(row![
+                    c->"Note",
+                    "Only has data while a transaction is executing"
+                ]);
+            }
+            SysvarAccount::LastRestartSlot(last_restart_slot) => {
+                sysvar_table
+                    .add_row(row![c->"Last Restart Slot", last_restart_slot.last_restart_slot]);
+            }
+        }
+
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+        let padded_width = width.saturating_sub(4);
+
+        let mut tables = Table::new();
+        tables.add_row(row![c->sysvar_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+        if let Some(list_table) = list_table {
+            tables.add_row(row![c->list_table]);
+            tables.add_row(row![" ".repeat(padded_width)]);
+        }
+        tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+        tables.printstd();
+    }
+
+    pub fn to_view(&self) -> SysvarView {
+        match self {
+            SysvarAccount::Clock(clock) => SysvarView::Clock {
+                slot: clock.slot,
+                epoch: clock.epoch,
+                leader_schedule_epoch: clock.leader_schedule_epoch,
+                epoch_start_timestamp: clock.epoch_start_timestamp,
+                unix_timestamp: clock.unix_timestamp,
+            },
+            SysvarAccount::Rent(rent) => SysvarView::Rent {
+                lamports_per_byte_year: rent.lamports_per_byte_year,
+                exemption_threshold: rent.exemption_threshold,
+                burn_percent: rent.burn_percent,
+            },
+            SysvarAccount::EpochSchedule(schedule) => SysvarView::EpochSchedule {
+                slots_per_epoch: schedule.slots_per_epoch,
+                leader_schedule_slot_offset: schedule.leader_schedule_slot_offset,
+                warmup: schedule.warmup,
+                first_normal_epoch: schedule.first_normal_epoch,
+                first_normal_slot: schedule.first_normal_slot,
+            },
+            SysvarAccount::EpochRewards(rewards) => SysvarView::EpochRewards {
+                total_rewards: rewards.total_rewards,
+                distributed_rewards: rewards.distributed_rewards,
+                distribution_complete_block_height: rewards.distribution_complete_block_height,
+            },
+            SysvarAccount::SlotHashes(slot_hashes) => SysvarView::SlotHashes {
+                entries: slot_hashes
+                    .iter()
+                    .map(|(slot, hash)| SlotHashView {
+                        slot: *slot,
+                        hash: hash.to_string(),
+                    })
+                    .collect(),
+            },
+            SysvarAccount::StakeHistory(stake_history) => SysvarView::StakeHistory {
+                entries: stake_history
+                    .iter()
+                    .map(|(epoch, entry)| StakeHistoryEntryView {
+                        epoch: *epoch,
+                        effective: entry.effective,
+                        activating: entry.activating,
+                        deactivating: entry.deactivating,
+                    })
+                    .collect(),
+            },
+            SysvarAccount::RecentBlockhashes(recent_blockhashes) => SysvarView::RecentBlockhashes {
+                entries: recent_blockhashes
+                    .iter()
+                    .map(|entry| RecentBlockhashView {
+                        blockhash: entry.blockhash.to_string(),
+                        lamports_per_signature: entry.fee_calculator.lamports_per_signature,
+                    })
+                    .collect(),
+            },
+            SysvarAccount::Fees(fees) => SysvarView::Fees {
+                lamports_per_signature: fees.fee_calculator.lamports_per_signature,
+            },
+            SysvarAccount::Instructions => SysvarView::Instructions,
+            SysvarAccount::LastRestartSlot(last_restart_slot) => SysvarView::LastRestartSlot {
+                last_restart_slot: last_restart_slot.last_restart_slot,
+            },
+        }
+    }
+}
+
+/// Serializable view model of a [`SysvarAccount`]. Amounts are in lamports.
+#[derive(Serialize)]
+#[serde(tag = "sysvar", rename_all = "snake_case")]
+pub enum SysvarView {
+    Clock {
+        slot: Slot,
+        epoch: Epoch,
+        leader_schedule_epoch: Epoch,
+        epoch_start_timestamp: i64,
+        unix_timestamp: i64,
+    },
+    Rent {
+        lamports_per_byte_year: u64,
+        exemption_threshold: f64,
+        burn_percent: u8,
+    },
+    EpochSchedule {
+        slots_per_epoch: u64,
+        leader_schedule_slot_offset: u64,
+        warmup: bool,
+        first_normal_epoch: Epoch,
+        first_normal_slot: Slot,
+    },
+    EpochRewards {
+        total_rewards: u64,
+        distributed_rewards: u64,
+        distribution_complete_block_height: u64,
+    },
+    /// Most recent slot first
+    SlotHashes {
+        entries: Vec<SlotHashView>,
+    },
+    /// Most recent epoch first
+    StakeHistory {
+        entries: Vec<StakeHistoryEntryView>,
+    },
+    /// Most recent blockhash first
+    RecentBlockhashes {
+        entries: Vec<RecentBlockhashView>,
+    },
+    Fees {
+        lamports_per_signature: u64,
+    },
+    Instructions,
+    LastRestartSlot {
+        last_restart_slot: Slot,
+    },
+}
+
+#[derive(Serialize)]
+pub struct SlotHashView {
+    pub slot: Slot,
+    pub hash: String,
+}
+
+#[derive(Serialize)]
+pub struct StakeHistoryEntryView {
+    pub epoch: Epoch,
+    pub effective: u64,
+    pub activating: u64,
+    pub deactivating: u64,
+}
+
+#[derive(Serialize)]
+pub struct RecentBlockhashView {
+    pub blockhash: String,
+    pub lamports_per_signature: u64,
+}
+
+fn decode<S: Sysvar>(account: &Account, name: &str) -> Result<S> {
+    from_account(account).ok_or_else(|| ExplorerError::Decode(format!("{name} sysvar")))
+}
+
+/// Whole SOL amounts, since stake history amounts are large
+fn format_sol(lamports: u64) -> String {
+    format!(
+        "◎{}",
+        (lamports / 1_000_000_000).to_formatted_string(&Locale::en)
+    )
+}
@@ -1,14 +1,107 @@
 //! Parsing token and token22 accounts
 
+use std::collections::{BTreeSet, HashMap};
+
 use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
-use solana_client::nonblocking::rpc_client::RpcClient as Client;
-use solana_sdk::{account::Account, program_option::COption, program_pack::Pack, pubkey::Pubkey};
-use spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType};
+use serde::Serialize;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient as Client, rpc_request::MAX_MULTIPLE_ACCOUNTS,
+};
+use solana_sdk::{
+    account::Account, clock::Epoch, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+};
+use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
 use spl_type_length_value::variable_len_pack::VariableLenPack;
 
-use crate::utils::display_balance;
+use crate::{
+    error::{ExplorerError, Result},
+    utils::display_balance,
+};
+
+use super::{
+    metaplex::{MetaplexMetadata, MetaplexMetadataView},
+    token_extensions::{
+        extensions_table, has_transfer_fee_config, parse_extensions, TokenExtension,
+    },
+    ParsedAccount, ParsedAccountView,
+};
+
+/// The accounts that token program accounts are decoded with, fetched once
+/// for a whole batch of accounts rather than once per account
+#[derive(Default)]
+pub struct PrefetchedMints {
+    /// Mints of token accounts
+    pub mints: HashMap<Pubkey, Account>,
+    /// Metaplex metadata of token accounts' mints and of mints, keyed by mint
+    pub metadata: HashMap<Pubkey, MetaplexMetadata>,
+    /// The current epoch, only fetched for token22 mints with a transfer fee
+    pub epoch: Option<Epoch>,
+}
+
+impl PrefetchedMints {
+    pub async fn fetch<'a>(
+        accounts: impl IntoIterator<Item = (&'a Pubkey, &'a Account)>,
+        client: &Client,
+    ) -> Result<PrefetchedMints> {
+        let mut mint_keys = BTreeSet::new();
+        let mut metadata_keys = BTreeSet::new();
+        let mut needs_epoch = false;
+        for (key, account) in accounts {
+            if account.owner == spl_token::ID {
+                if let Ok(token_account) = spl_token::state::Account::unpack(&account.data) {
+                    mint_keys.insert(token_account.mint);
+                } else if spl_token::state::Mint::unpack(&account.data).is_ok() {
+                    metadata_keys.insert(*key);
+                }
+            } else if account.owner == spl_token_2022::ID {
+                if let Ok(token_account) =
+                    StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+                {
+                    mint_keys.insert(token_account.base.mint);
+                } else if StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data)
+                    .is_ok()
+                {
+                    metadata_keys.insert(*key);
+                    needs_epoch |= has_transfer_fee_config(&account.data);
+                }
+            }
+        }
+
+        let mint_keys: Vec<Pubkey> = mint_keys.into_iter().collect();
+        let mut mints = HashMap::with_capacity(mint_keys.len());
+        for keys in mint_keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = client.get_multiple_accounts(keys).await?;
+            for (key, account) in keys.iter().zip(accounts) {
+                if let Some(account) = account {
+                    mints.insert(*key, account);
+                }
+            }
+        }
+
+        metadata_keys.extend(mint_keys);
+        let metadata_keys: Vec<Pubkey> = metadata_keys.into_iter().collect();
+        let metadata = MetaplexMetadata::fetch_many(&metadata_keys, client).await?;
 
-use super::ParsedAccount;
+        let epoch = if needs_epoch {
+            Some(client.get_epoch_info().await?.epoch)
+        } else {
+            None
+        };
+
+        Ok(PrefetchedMints {
+            mints,
+            metadata,
+            epoch,
+        })
+    }
+
+    fn mint_data(&self, mint: &Pubkey) -> Result<&[u8]> {
+        self.mints
+            .get(mint)
+            .map(|account| account.data.as_slice())
+            .ok_or_else(|| ExplorerError::NotFound(format!("mint {mint} not found")))
+    }
+}
 
 pub enum TokenProgramAccount {
     Tokenkeg(TokenkegAccount),
@@ -16,61 +109,80 @@ pub enum TokenProgramAccount {
 }
 
 impl TokenProgramAccount {
-    pub async fn parse<'a>(account: &'a Account, client: &Client) -> Option<ParsedAccount<'a>> {
+    pub async fn parse<'a>(
+        account: &'a Account,
+        key: &Pubkey,
+        client: &Client,
+    ) -> Result<Option<ParsedAccount<'a>>> {
+        let prefetched = PrefetchedMints::fetch([(key, account)], client).await?;
+        TokenProgramAccount::parse_prefetched(account, key, &prefetched)
+    }
+
+    /// Parses a token program account, taking the mint of token accounts
+    /// (and the metadata of mints) from `prefetched` rather than the rpc
+    pub fn parse_prefetched<'a>(
+        account: &'a Account,
+        key: &Pubkey,
+        prefetched: &PrefetchedMints,
+    ) -> Result<Option<ParsedAccount<'a>>> {
         // Check account owner for supported token programs
         if account.owner == spl_token::ID {
             // First try parse tokenkeg token account
             if let Ok(token_account) = spl_token::state::Account::unpack(&account.data) {
-                // Fetch mint account
-                let mint_account_data = client.get_account_data(&token_account.mint).await.unwrap();
-                let mint_account = spl_token::state::Mint::unpack(&mint_account_data).unwrap();
-
-                // Try to fetch metadata
-                let mpl_metadata_key =
-                    mpl_token_metadata::accounts::Metadata::find_pda(&token_account.mint).0;
-                let symbol = client
-                    .get_account_data(&mpl_metadata_key)
-                    .await
-                    .map(|data| {
-                        mpl_token_metadata::accounts::Metadata::from_bytes(&data)
-                            .unwrap()
-                            .symbol
-                    })
-                    .ok();
+                let mint_account_data = prefetched.mint_data(&token_account.mint)?;
+                let mint_account =
+                    spl_token::state::Mint::unpack(mint_account_data).map_err(|e| {
+                        ExplorerError::Decode(format!("mint {}: {e}", token_account.mint))
+                    })?;
+
+                let metadata = prefetched
+                    .metadata
+                    .get(&token_account.mint)
+                    .map(|metaplex| &metaplex.metadata);
+                let symbol = metadata.map(|metadata| metadata.symbol.clone());
+                let uri = metadata.map(|metadata| metadata.uri.clone());
 
-                return Some(ParsedAccount::tokenkeg_token(
+                return Ok(Some(ParsedAccount::tokenkeg_token(
                     token_account,
                     mint_account,
                     symbol,
-                ));
+                    uri,
+                )));
             }
 
             // Then try parsing tokenkeg mint account
             if let Ok(mint_account) = spl_token::state::Mint::unpack(&account.data) {
-                return Some(ParsedAccount::tokenkeg_mint(mint_account));
+                let metadata = prefetched.metadata.get(key).cloned();
+                return Ok(Some(ParsedAccount::tokenkeg_mint(mint_account, metadata)));
+            }
+
+            // Then try parsing tokenkeg multisig, which may be uninitialized
+            if let Ok(multisig) = spl_token::state::Multisig::unpack_unchecked(&account.data) {
+                return Ok(Some(ParsedAccount::TokenProgram(Box::new(
+                    TokenProgramAccount::Tokenkeg(TokenkegAccount::Multisig(multisig)),
+                ))));
             }
         } else if account.owner == spl_token_2022::ID {
             // First try parse token22 token account
-            if let Ok(token_account) = spl_token_2022::state::Account::unpack(&account.data) {
-                // Fetch mint account
-                let mint_account_data = client.get_account_data(&token_account.mint).await.unwrap();
-                let mint_account = spl_token_2022::extension::StateWithExtensions::<
-                    spl_token_2022::state::Mint,
-                >::unpack(&mint_account_data)
-                .unwrap();
-
-                // Try to fetch metadata
-                let mpl_metadata_key =
-                    mpl_token_metadata::accounts::Metadata::find_pda(&token_account.mint).0;
-                let mut symbol = client
-                    .get_account_data(&mpl_metadata_key)
-                    .await
-                    .map(|data| {
-                        mpl_token_metadata::accounts::Metadata::from_bytes(&data)
-                            .unwrap()
-                            .symbol
-                    })
-                    .ok();
+            if let Ok(token_account) =
+                StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            {
+                let extensions = parse_extensions(&token_account, prefetched.epoch)?;
+                let token_account = token_account.base;
+
+                let mint_account_data = prefetched.mint_data(&token_account.mint)?;
+                let mint_account =
+                    StateWithExtensions::<spl_token_2022::state::Mint>::unpack(mint_account_data)
+                        .map_err(|e| {
+                        ExplorerError::Decode(format!("mint {}: {e}", token_account.mint))
+                    })?;
+
+                let metadata = prefetched
+                    .metadata
+                    .get(&token_account.mint)
+                    .map(|metaplex| &metaplex.metadata);
+                let mut symbol = metadata.map(|metadata| metadata.symbol.clone());
+                let mut uri = metadata.map(|metadata| metadata.uri.clone());
 
                 // If not mpl, try token-2022
                 if symbol.is_none() {
@@ -80,29 +192,76 @@ impl TokenProgramAccount {
                         .and_then(<TokenMetadata as VariableLenPack>::unpack_from_slice)
                     {
                         symbol.replace(token_metadata.symbol);
+                        uri.replace(token_metadata.uri);
                     }
                 }
 
-                return Some(ParsedAccount::token22_token(
+                return Ok(Some(ParsedAccount::token22_token(
                     token_account,
                     mint_account.base,
                     symbol,
-                ));
+                    uri,
+                    extensions,
+                )));
             }
 
             // Then try parsing token22 mint account
-            if let Ok(mint_account) = spl_token_2022::extension::StateWithExtensions::<
-                spl_token_2022::state::Mint,
-            >::unpack(&account.data)
+            if let Ok(mint_account) =
+                StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data)
             {
-                // Get extensions
-                let extensions = mint_account.get_extension_types().unwrap();
+                // Decode extensions
+                let extensions = parse_extensions(&mint_account, prefetched.epoch)?;
+                let metadata = prefetched.metadata.get(key).cloned();
 
-                return Some(ParsedAccount::token22_mint(mint_account.base, extensions));
+                return Ok(Some(ParsedAccount::token22_mint(
+                    mint_account.base,
+                    extensions,
+                    metadata,
+                )));
+            }
+
+            // Then try parsing token22 multisig, which may be uninitialized.
+            // Token22 never sizes accounts or mints to the multisig length.
+            if let Ok(multisig) = spl_token_2022::state::Multisig::unpack_unchecked(&account.data) {
+                return Ok(Some(ParsedAccount::TokenProgram(Box::new(
+                    TokenProgramAccount::Token22(Token22Account::Multisig(multisig)),
+                ))));
             }
         }
 
-        None
+        Ok(None)
+    }
+
+    /// The URI of the off-chain metadata of the mint, or of the token
+    /// account's mint
+    pub fn metadata_uri(&self) -> Option<&str> {
+        let uri = match self {
+            TokenProgramAccount::Tokenkeg(TokenkegAccount::TokenAccount { uri, .. })
+            | TokenProgramAccount::Token22(Token22Account::TokenAccount { uri, .. }) => {
+                uri.as_deref()
+            }
+            TokenProgramAccount::Tokenkeg(TokenkegAccount::MintAccount { metadata, .. }) => {
+                metadata
+                    .as_ref()
+                    .map(|metaplex| metaplex.metadata.uri.as_str())
+            }
+            TokenProgramAccount::Token22(Token22Account::MintAccount {
+                metadata,
+                extensions,
+                ..
+            }) => metadata
+                .as_ref()
+                .map(|metaplex| metaplex.metadata.uri.as_str())
+                .or_else(|| {
+                    extensions.iter().find_map(|extension| match extension {
+                        TokenExtension::TokenMetadata { uri, .. } => Some(uri.as_str()),
+                        _ => None,
+                    })
+                }),
+            TokenProgramAccount::Tokenkeg(TokenkegAccount::Multisig(_))
+            | TokenProgramAccount::Token22(Token22Account::Multisig(_)) => None,
+        };
+        uri.filter(|uri| !uri.trim().is_empty())
     }
 
     pub fn display(self, key: &Pubkey) {
@@ -112,20 +271,32 @@ impl TokenProgramAccount {
                     token_account,
                     mint_account,
                     symbol,
+                    ..
                 } => print_token_account(
                     key,
-                    token_account.amount,
+                    &token_account.into(),
                     mint_account.decimals,
-                    &token_account.mint,
                     symbol,
+                    &[],
                 ),
-                TokenkegAccount::MintAccount(mint_account) => print_mint_account(
+                TokenkegAccount::MintAccount {
+                    mint_account,
+                    metadata,
+                } => print_mint_account(
                     key,
                     mint_account.supply,
                     mint_account.decimals,
                     unwrap_coption_pubkey(mint_account.mint_authority),
                     unwrap_coption_pubkey(mint_account.freeze_authority),
                     &[],
+                    metadata.as_ref(),
+                ),
+                TokenkegAccount::Multisig(multisig) => print_multisig_account(
+                    key,
+                    multisig.m,
+                    multisig.n,
+                    multisig.is_initialized,
+                    &multisig.signers,
                 ),
             },
             TokenProgramAccount::Token22(account) => match account {
@@ -133,16 +304,19 @@ impl TokenProgramAccount {
                     token_account,
                     mint_account,
                     symbol,
+                    extensions,
+                    ..
                 } => print_token_account(
                     key,
-                    token_account.amount,
+                    &token_account.into(),
                     mint_account.decimals,
-                    &token_account.mint,
                     symbol,
+                    &extensions,
                 ),
                 Token22Account::MintAccount {
                     mint_account,
                     extensions,
+                    metadata,
                 } => print_mint_account(
                     key,
                     mint_account.supply,
@@ -150,10 +324,285 @@ impl TokenProgramAccount {
                     unwrap_coption_pubkey(mint_account.mint_authority),
                     unwrap_coption_pubkey(mint_account.freeze_authority),
                     &extensions,
+                    metadata.as_ref(),
+                ),
+                Token22Account::Multisig(multisig) => print_multisig_account(
+                    key,
+                    multisig.m,
+                    multisig.n,
+                    multisig.is_initialized,
+                    &multisig.signers,
                 ),
             },
         }
     }
+
+    pub fn to_view(&self, key: &Pubkey) -> ParsedAccountView {
+        match self {
+            TokenProgramAccount::Tokenkeg(account) => match account {
+                TokenkegAccount::TokenAccount {
+                    token_account,
+                    mint_account,
+                    symbol,
+                    ..
+                } => ParsedAccountView::TokenAccount(TokenAccountView::new(
+                    key,
+                    &(*token_account).into(),
+                    mint_account.decimals,
+                    symbol.clone(),
+                    vec![],
+                )),
+                TokenkegAccount::MintAccount {
+                    mint_account,
+                    metadata,
+                } => ParsedAccountView::Mint(MintAccountView {
+                    program: "spl-token",
+                    decimals: mint_account.decimals,
+                    supply: mint_account.supply.to_string(),
+                    ui_supply: display_balance(mint_account.supply, mint_account.decimals as usize),
+                    mint_authority: coption_pubkey_to_string(mint_account.mint_authority),
+                    freeze_authority: coption_pubkey_to_string(mint_account.freeze_authority),
+                    extensions: vec![],
+                    metadata: metadata.as_ref().map(MetaplexMetadata::to_view),
+                }),
+                TokenkegAccount::Multisig(multisig) => {
+                    ParsedAccountView::TokenMultisig(MultisigAccountView::new(
+                        "spl-token",
+                        multisig.m,
+                        multisig.n,
+                        multisig.is_initialized,
+                        &multisig.signers,
+                    ))
+                }
+            },
+            TokenProgramAccount::Token22(account) => match account {
+                Token22Account::TokenAccount {
+                    token_account,
+                    mint_account,
+                    symbol,
+                    extensions,
+                    ..
+                } => ParsedAccountView::TokenAccount(TokenAccountView::new(
+                    key,
+                    &(*token_account).into(),
+                    mint_account.decimals,
+                    symbol.clone(),
+                    extensions.clone(),
+                )),
+                Token22Account::MintAccount {
+                    mint_account,
+                    extensions,
+                    metadata,
+                } => ParsedAccountView::Mint(MintAccountView {
+                    program: "spl-token-2022",
+                    decimals: mint_account.decimals,
+                    supply: mint_account.supply.to_string(),
+                    ui_supply: display_balance(mint_account.supply, mint_account.decimals as usize),
+                    mint_authority: coption_pubkey_to_string(mint_account.mint_authority),
+                    freeze_authority: coption_pubkey_to_string(mint_account.freeze_authority),
+                    extensions: extensions.clone(),
+                    metadata: metadata.as_ref().map(MetaplexMetadata::to_view),
+                }),
+                Token22Account::Multisig(multisig) => {
+                    ParsedAccountView::TokenMultisig(MultisigAccountView::new(
+                        "spl-token-2022",
+                        multisig.m,
+                        multisig.n,
+                        multisig.is_initialized,
+                        &multisig.signers,
+                    ))
+                }
+            },
+        }
+    }
+}
+
+/// The state of a token account, common to both token programs
+pub struct TokenAccountDetails {
+    pub program_id: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub delegate: Option<Pubkey>,
+    pub delegated_amount: u64,
+    pub state: &'static str,
+    /// The rent-exempt reserve of native (wrapped SOL) accounts. The wrapped
+    /// lamports are the account's amount.
+    pub rent_exempt_reserve: Option<u64>,
+    pub close_authority: Option<Pubkey>,
+}
+
+impl From<spl_token::state::Account> for TokenAccountDetails {
+    fn from(account: spl_token::state::Account) -> TokenAccountDetails {
+        use spl_token::state::AccountState;
+        TokenAccountDetails {
+            program_id: spl_token::ID,
+            mint: account.mint,
+            owner: account.owner,
+            amount: account.amount,
+            delegate: account.delegate.into(),
+            delegated_amount: account.delegated_amount,
+            state: match account.state {
+                AccountState::Uninitialized => "Uninitialized",
+                AccountState::Initialized => "Initialized",
+                AccountState::Frozen => "Frozen",
+            },
+            rent_exempt_reserve: account.is_native.into(),
+            close_authority: account.close_authority.into(),
+        }
+    }
+}
+
+impl From<spl_token_2022::state::Account> for TokenAccountDetails {
+    fn from(account: spl_token_2022::state::Account) -> TokenAccountDetails {
+        use spl_token_2022::state::AccountState;
+        TokenAccountDetails {
+            program_id: spl_token_2022::ID,
+            mint: account.mint,
+            owner: account.owner,
+            amount: account.amount,
+            delegate: account.delegate.into(),
+            delegated_amount: account.delegated_amount,
+            state: match account.state {
+                AccountState::Uninitialized => "Uninitialized",
+                AccountState::Initialized => "Initialized",
+                AccountState::Frozen => "Frozen",
+            },
+            rent_exempt_reserve: account.is_native.into(),
+            close_authority: account.close_authority.into(),
+        }
+    }
+}
+
+impl TokenAccountDetails {
+    fn program_name(&self) -> &'static str {
+        if self.program_id == spl_token_2022::ID {
+            "spl-token-2022"
+        } else {
+            "spl-token"
+        }
+    }
+
+    /// Whether `key` is the owner's associated token account for the mint,
+    /// i.e. the account wallets and most programs send tokens to
+    fn is_associated_token_account(&self, key: &Pubkey) -> bool {
+        *key == spl_associated_token_account::get_associated_token_address_with_program_id(
+            &self.owner,
+            &self.mint,
+            &self.program_id,
+        )
+    }
+}
+
+/// Serializable view model of a token account.
+///
+/// Raw token amounts are strings, as in the RPC's `UiTokenAmount`, since they
+/// may not fit in a JSON number.
+#[derive(Serialize)]
+pub struct TokenAccountView {
+    pub program: &'static str,
+    pub mint: String,
+    pub owner: String,
+    pub symbol: Option<String>,
+    pub amount: String,
+    pub decimals: u8,
+    pub ui_amount: String,
+    pub state: &'static str,
+    pub delegate: Option<String>,
+    pub delegated_amount: String,
+    pub close_authority: Option<String>,
+    pub is_native: bool,
+    /// In lamports, only set for native (wrapped SOL) accounts
+    pub rent_exempt_reserve: Option<u64>,
+    /// Whether this is the owner's canonical associated token account
+    pub is_associated_token_account: bool,
+    pub extensions: Vec<TokenExtension>,
+}
+
+impl TokenAccountView {
+    fn new(
+        key: &Pubkey,
+        details: &TokenAccountDetails,
+        decimals: u8,
+        symbol: Option<String>,
+        extensions: Vec<TokenExtension>,
+    ) -> TokenAccountView {
+        TokenAccountView {
+            program: details.program_name(),
+            mint: details.mint.to_string(),
+            owner: details.owner.to_string(),
+            symbol,
+            amount: details.amount.to_string(),
+            decimals,
+            ui_amount: display_balance(details.amount, decimals as usize),
+            state: details.state,
+            delegate: details.delegate.as_ref().map(ToString::to_string),
+            delegated_amount: details.delegated_amount.to_string(),
+            close_authority: details.close_authority.as_ref().map(ToString::to_string),
+            is_native: details.rent_exempt_reserve.is_some(),
+            rent_exempt_reserve: details.rent_exempt_reserve,
+            is_associated_token_account: details.is_associated_token_account(key),
+            extensions,
+        }
+    }
+}
+
+/// Serializable view model of a mint account
+#[derive(Serialize)]
+pub struct MintAccountView {
+    pub program: &'static str,
+    pub decimals: u8,
+    pub supply: String,
+    pub ui_supply: String,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    pub extensions: Vec<TokenExtension>,
+    /// Metaplex metadata, if the mint has any
+    pub metadata: Option<MetaplexMetadataView>,
+}
+
+/// Serializable view model of a token multisig account
+#[derive(Serialize)]
+pub struct MultisigAccountView {
+    pub program: &'static str,
+    /// Number of signers required
+    pub m: u8,
+    /// Number of valid signers
+    pub n: u8,
+    pub initialized: bool,
+    pub signers: Vec<String>,
+}
+
+impl MultisigAccountView {
+    fn new(
+        program: &'static str,
+        m: u8,
+        n: u8,
+        initialized: bool,
+        signers: &[Pubkey],
+    ) -> MultisigAccountView {
+        MultisigAccountView {
+            program,
+            m,
+            n,
+            initialized,
+            signers: valid_signers(n, signers)
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// Only the first `n` of the signer slots are in use
+fn valid_signers(n: u8, signers: &[Pubkey]) -> &[Pubkey] {
+    &signers[..(n as usize).min(signers.len())]
+}
+
+fn coption_pubkey_to_string(pubkey: COption<Pubkey>) -> Option<String> {
+    Option::<Pubkey>::from(pubkey)
+        .as_ref()
+        .map(ToString::to_string)
 }
 
 fn unwrap_coption_pubkey(pubkey: COption<Pubkey>) -> Cow<'static, str> {
@@ -165,18 +614,60 @@ fn unwrap_coption_pubkey(pubkey: COption<Pubkey>) -> Cow<'static, str> {
 
 fn print_token_account(
     key: &Pubkey,
-    balance: u64,
+    details: &TokenAccountDetails,
     decimals: u8,
-    mint: &Pubkey,
     symbol: Option<String>,
+    extensions: &[TokenExtension],
 ) {
     let mut token_account_table = Table::new();
     token_account_table.set_titles(row![c->"Token Account", key]);
     if let Some(s) = symbol {
         token_account_table.add_row(row![c->"Symbol", s]);
     }
-    token_account_table.add_row(row![c->"Mint", mint]);
-    token_account_table.add_row(row![c->"Balance", display_balance(balance, decimals as usize)]);
+    token_account_table.add_row(row![c->"Mint", details.mint]);
+    token_account_table.add_row(row![c->"Owner", details.owner]);
+    token_account_table.add_row(row![
+        c->"Balance",
+        display_balance(details.amount, decimals as usize)
+    ]);
+    if let Some(reserve) = details.rent_exempt_reserve {
+        token_account_table.add_row(row![c->"Native", "Yes (wrapped SOL)"]);
+        token_account_table.add_row(row![c->"Rent-Exempt Reserve", display_balance(reserve, 9)]);
+    }
+
+    // Highlight the states that restrict or share control of the tokens
+    if details.state == "Frozen" {
+        token_account_table.add_row(row![c->"State", Fr->details.state]);
+    } else {
+        token_account_table.add_row(row![c->"State", details.state]);
+    }
+    match details.delegate {
+        Some(delegate) => {
+            token_account_table.add_row(row![c->"Delegate", Fy->delegate]);
+            token_account_table.add_row(row![
+                c->"Delegated Amount",
+                Fy->display_balance(details.delegated_amount, decimals as usize)
+            ]);
+        }
+        None => {
+            token_account_table.add_row(row![c->"Delegate", "None"]);
+        }
+    }
+    token_account_table.add_row(row![
+        c->"Close Authority",
+        details
+            .close_authority
+            .map(|authority| authority.to_string())
+            .unwrap_or("None".to_string())
+    ]);
+    token_account_table.add_row(row![
+        c->"Associated Token Account",
+        if details.is_associated_token_account(key) {
+            "Yes"
+        } else {
+            "No (not the owner's canonical account)"
+        }
+    ]);
 
     use terminal_size::{terminal_size, Width};
     let size = terminal_size();
@@ -186,6 +677,10 @@ fn print_token_account(
     let mut tables = Table::new();
     tables.add_row(row![c->token_account_table]);
     tables.add_row(row![" ".repeat(padded_width)]);
+    if let Some(extensions_table) = extensions_table(extensions, decimals) {
+        tables.add_row(row![c->extensions_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+    }
     tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
     tables.printstd();
 }
@@ -196,7 +691,8 @@ fn print_mint_account(
     decimals: u8,
     mint_authority_key: Cow<'static, str>,
     freeze_authority_key: Cow<'static, str>,
-    extensions: &[ExtensionType],
+    extensions: &[TokenExtension],
+    metadata: Option<&MetaplexMetadata>,
 ) {
     let mut mint_account_table = Table::new();
     mint_account_table.set_titles(row![c->"Mint Account", key]);
@@ -204,9 +700,6 @@ fn print_mint_account(
     mint_account_table.add_row(row![c->"Supply", display_balance(supply, decimals as usize)]);
     mint_account_table.add_row(row![c->"Mint Authority", mint_authority_key]);
     mint_account_table.add_row(row![c->"Freeze Authority", freeze_authority_key]);
-    for (i, ext) in extensions.into_iter().enumerate() {
-        mint_account_table.add_row(row![c->format!("Extension {}", i + 1), format!("{ext:?}")]);
-    }
 
     use terminal_size::{terminal_size, Width};
     let size = terminal_size();
@@ -216,6 +709,35 @@ fn print_mint_account(
     let mut tables = Table::new();
     tables.add_row(row![c->mint_account_table]);
     tables.add_row(row![" ".repeat(padded_width)]);
+    if let Some(extensions_table) = extensions_table(extensions, decimals) {
+        tables.add_row(row![c->extensions_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+    }
+    if let Some(metadata) = metadata {
+        tables.add_row(row![c->metadata.table()]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+    }
+    tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+    tables.printstd();
+}
+
+fn print_multisig_account(key: &Pubkey, m: u8, n: u8, initialized: bool, signers: &[Pubkey]) {
+    let mut multisig_table = Table::new();
+    multisig_table.set_titles(row![c->"Token Multisig", key]);
+    multisig_table.add_row(row![c->"Required Signers", format!("{m} of {n}")]);
+    multisig_table.add_row(row![c->"Initialized", initialized]);
+    for (i, signer) in valid_signers(n, signers).iter().enumerate() {
+        multisig_table.add_row(row![c->format!("Signer {}", i + 1), signer]);
+    }
+
+    use terminal_size::{terminal_size, Width};
+    let size = terminal_size();
+    let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+    let padded_width = width.saturating_sub(4);
+
+    let mut tables = Table::new();
+    tables.add_row(row![c->multisig_table]);
+    tables.add_row(row![" ".repeat(padded_width)]);
     tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
     tables.printstd();
 }
@@ -225,8 +747,14 @@ pub enum TokenkegAccount {
         token_account: spl_token::state::Account,
         mint_account: spl_token::state::Mint,
         symbol: Option<String>,
+        /// URI of the mint's off-chain metadata
+        uri: Option<String>,
     },
-    MintAccount(spl_token::state::Mint),
+    MintAccount {
+        mint_account: spl_token::state::Mint,
+        metadata: Option<MetaplexMetadata>,
+    },
+    Multisig(spl_token::state::Multisig),
 }
 
 pub enum Token22Account {
@@ -234,14 +762,19 @@ pub enum Token22Account {
         token_account: spl_token_2022::state::Account,
         mint_account: spl_token_2022::state::Mint,
         symbol: Option<String>,
+        /// URI of the mint's off-chain metadata
+        uri: Option<String>,
+        extensions: Vec<TokenExtension>,
     },
     MintAccount {
         mint_account: spl_token_2022::state::Mint,
-        extensions: Vec<ExtensionType>,
+        extensions: Vec<TokenExtension>,
+        metadata: Option<MetaplexMetadata>,
     },
+    Multisig(spl_token_2022::state::Multisig),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct TokenAccountBalance {
     pub key: String,
     pub balance: UiAmount,
@@ -252,10 +785,12 @@ pub struct TokenAccountBalance {
 
 type UiAmount = String;
 
-use std::{borrow::Cow, str::FromStr};
+use std::borrow::Cow;
 macro_rules! from_str {
-    ($x:expr) => {
-        FromStr::from_str(&$x.as_str().unwrap()).unwrap()
+    ($x:expr, $key:expr) => {
+        $x.as_str()
+            .map(ToString::to_string)
+            .ok_or_else(|| ExplorerError::Decode(format!("token account {}", $key)))?
     };
 }
 
@@ -263,27 +798,30 @@ impl TokenAccountBalance {
     pub(crate) fn parse_validated_json(
         json: solana_account_decoder::parse_account_data::ParsedAccount,
         key: String,
-    ) -> TokenAccountBalance {
+    ) -> Result<TokenAccountBalance> {
         let info = &json.parsed["info"];
 
         if json.program == "spl-token" {
-            TokenAccountBalance {
-                key,
+            Ok(TokenAccountBalance {
                 program: "spl-token",
-                balance: from_str!(info["tokenAmount"]["uiAmountString"]),
-                mint: from_str!(info["mint"]),
+                balance: from_str!(info["tokenAmount"]["uiAmountString"], key),
+                mint: from_str!(info["mint"], key),
                 symbol: None,
-            }
-        } else if json.program == "spl-token-2022" {
-            TokenAccountBalance {
                 key,
-                program: "spl-token",
-                balance: from_str!(info["tokenAmount"]["uiAmountString"]),
-                mint: from_str!(info["mint"]),
+            })
+        } else if json.program == "spl-token-2022" {
+            Ok(TokenAccountBalance {
+                program: "spl-token-2022",
+                balance: from_str!(info["tokenAmount"]["uiAmountString"], key),
+                mint: from_str!(info["mint"], key),
                 symbol: None,
-            }
+                key,
+            })
         } else {
-            unimplemented!("scaffolded for other token programs... {}", json.program)
+            Err(ExplorerError::Decode(format!(
+                "token account {key}: unsupported token program {}",
+                json.program
+            )))
         }
     }
 }
@@ -0,0 +1,681 @@
+//! Decoding the state of token22 mint and token account extensions
+
+use std::borrow::Cow;
+
+use colored::Colorize;
+use prettytable::{row, Table};
+use serde::Serialize;
+use solana_sdk::{clock::Epoch, program_error::ProgramError, pubkey::Pubkey};
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+        confidential_transfer_fee::ConfidentialTransferFeeConfig,
+        cpi_guard::CpiGuard,
+        default_account_state::DefaultAccountState,
+        group_member_pointer::GroupMemberPointer,
+        group_pointer::GroupPointer,
+        interest_bearing_mint::InterestBearingConfig,
+        memo_transfer::MemoTransfer,
+        metadata_pointer::MetadataPointer,
+        mint_close_authority::MintCloseAuthority,
+        permanent_delegate::PermanentDelegate,
+        transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+        transfer_hook::{TransferHook, TransferHookAccount},
+        BaseState, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    solana_zk_token_sdk::zk_token_elgamal::pod::ElGamalPubkey,
+};
+use spl_token_group_interface::state::{TokenGroup, TokenGroupMember};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+use crate::{
+    error::{ExplorerError, Result},
+    utils::{display_balance, format_basis_points},
+};
+
+/// The decoded state of a single extension.
+///
+/// Pubkeys are `null` when unset, and raw token amounts are strings like
+/// elsewhere in the token views. Confidential balances are encrypted, so only
+/// their settings are shown.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "extension", rename_all = "snake_case")]
+pub enum TokenExtension {
+    TransferFeeConfig {
+        transfer_fee_config_authority: Option<String>,
+        withdraw_withheld_authority: Option<String>,
+        withheld_amount: String,
+        /// The fee in effect for the current epoch
+        current_fee: TransferFeeView,
+        /// A scheduled fee, which takes effect at its epoch
+        next_fee: Option<TransferFeeView>,
+    },
+    TransferFeeAmount {
+        withheld_amount: String,
+    },
+    MintCloseAuthority {
+        close_authority: Option<String>,
+    },
+    ConfidentialTransferMint {
+        authority: Option<String>,
+        auto_approve_new_accounts: bool,
+        auditor_elgamal_pubkey: Option<String>,
+    },
+    ConfidentialTransferAccount {
+        approved: bool,
+        elgamal_pubkey: String,
+        allow_confidential_credits: bool,
+        allow_non_confidential_credits: bool,
+        pending_balance_credit_counter: u64,
+        maximum_pending_balance_credit_counter: u64,
+    },
+    ConfidentialTransferFeeConfig {
+        authority: Option<String>,
+        withdraw_withheld_authority_elgamal_pubkey: String,
+        harvest_to_mint_enabled: bool,
+    },
+    ConfidentialTransferFeeAmount,
+    DefaultAccountState {
+        state: &'static str,
+    },
+    ImmutableOwner,
+    MemoTransfer {
+        require_incoming_transfer_memos: bool,
+    },
+    NonTransferable,
+    NonTransferableAccount,
+    InterestBearingConfig {
+        rate_authority: Option<String>,
+        /// Annual rates, in basis points
+        current_rate: i16,
+        pre_update_average_rate: i16,
+        initialization_timestamp: i64,
+        last_update_timestamp: i64,
+    },
+    CpiGuard {
+        lock_cpi: bool,
+    },
+    PermanentDelegate {
+        delegate: Option<String>,
+    },
+    TransferHook {
+        authority: Option<String>,
+        program_id: Option<String>,
+    },
+    TransferHookAccount {
+        transferring: bool,
+    },
+    MetadataPointer {
+        authority: Option<String>,
+        metadata_address: Option<String>,
+    },
+    TokenMetadata {
+        update_authority: Option<String>,
+        mint: String,
+        name: String,
+        symbol: String,
+        uri: String,
+        additional_metadata: Vec<(String, String)>,
+    },
+    GroupPointer {
+        authority: Option<String>,
+        group_address: Option<String>,
+    },
+    TokenGroup {
+        update_authority: Option<String>,
+        mint: String,
+        size: u32,
+        max_size: u32,
+    },
+    GroupMemberPointer {
+        authority: Option<String>,
+        member_address: Option<String>,
+    },
+    TokenGroupMember {
+        mint: String,
+        group: String,
+        member_number: u32,
+    },
+    /// An extension this version of the explorer can't decode
+    Unknown {
+        extension_type: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransferFeeView {
+    pub epoch: Epoch,
+    pub transfer_fee_basis_points: u16,
+    pub maximum_fee: String,
+}
+
+impl From<&TransferFee> for TransferFeeView {
+    fn from(fee: &TransferFee) -> TransferFeeView {
+        TransferFeeView {
+            epoch: u64::from(fee.epoch),
+            transfer_fee_basis_points: u16::from(fee.transfer_fee_basis_points),
+            maximum_fee: u64::from(fee.maximum_fee).to_string(),
+        }
+    }
+}
+
+/// Whether `data` is a token22 mint with a transfer fee, whose decoding
+/// depends on the current epoch
+pub fn has_transfer_fee_config(data: &[u8]) -> bool {
+    StateWithExtensions::<spl_token_2022::state::Mint>::unpack(data)
+        .is_ok_and(|mint| mint.get_extension::<TransferFeeConfig>().is_ok())
+}
+
+/// Decodes every extension on a mint or token account. `epoch` is the current
+/// epoch, which is only needed (see [`has_transfer_fee_config`]) to tell which
+/// transfer fee is in effect.
+pub fn parse_extensions<S: BaseState>(
+    state: &StateWithExtensions<'_, S>,
+    epoch: Option<Epoch>,
+) -> Result<Vec<TokenExtension>> {
+    let extension_types = state
+        .get_extension_types()
+        .map_err(|e| ExplorerError::Decode(format!("token extensions: {e}")))?;
+
+    let mut extensions = Vec::with_capacity(extension_types.len());
+    for extension_type in extension_types {
+        // A malformed extension shouldn't hide the rest of the account
+        let extension = parse_extension(state, extension_type, epoch).unwrap_or_else(|e| {
+            eprintln!("{} {e}", "warning:".yellow().bold());
+            TokenExtension::Unknown {
+                extension_type: format!("{extension_type:?}"),
+            }
+        });
+        extensions.push(extension);
+    }
+
+    Ok(extensions)
+}
+
+/// Decodes a single extension of `state`
+fn parse_extension<S: BaseState>(
+    state: &StateWithExtensions<'_, S>,
+    extension_type: ExtensionType,
+    epoch: Option<Epoch>,
+) -> Result<TokenExtension> {
+    let decode_err =
+        |e: ProgramError| ExplorerError::Decode(format!("{extension_type:?} extension: {e}"));
+
+    let extension = match extension_type {
+        ExtensionType::TransferFeeConfig => {
+            let config = state
+                .get_extension::<TransferFeeConfig>()
+                .map_err(decode_err)?;
+            let epoch = epoch.ok_or_else(|| {
+                ExplorerError::Decode("TransferFeeConfig extension: unknown epoch".to_string())
+            })?;
+
+            // The newer fee only takes effect once its epoch is reached
+            let newer = &config.newer_transfer_fee;
+            let (current_fee, next_fee) = if u64::from(newer.epoch) <= epoch {
+                (newer.into(), None)
+            } else {
+                ((&config.older_transfer_fee).into(), Some(newer.into()))
+            };
+
+            TokenExtension::TransferFeeConfig {
+                transfer_fee_config_authority: optional_pubkey(
+                    config.transfer_fee_config_authority.into(),
+                ),
+                withdraw_withheld_authority: optional_pubkey(
+                    config.withdraw_withheld_authority.into(),
+                ),
+                withheld_amount: u64::from(config.withheld_amount).to_string(),
+                current_fee,
+                next_fee,
+            }
+        }
+        ExtensionType::TransferFeeAmount => {
+            let amount = state
+                .get_extension::<TransferFeeAmount>()
+                .map_err(decode_err)?;
+            TokenExtension::TransferFeeAmount {
+                withheld_amount: u64::from(amount.withheld_amount).to_string(),
+            }
+        }
+        ExtensionType::MintCloseAuthority => {
+            let authority = state
+                .get_extension::<MintCloseAuthority>()
+                .map_err(decode_err)?;
+            TokenExtension::MintCloseAuthority {
+                close_authority: optional_pubkey(authority.close_authority.into()),
+            }
+        }
+        ExtensionType::ConfidentialTransferMint => {
+            let mint = state
+                .get_extension::<ConfidentialTransferMint>()
+                .map_err(decode_err)?;
+            TokenExtension::ConfidentialTransferMint {
+                authority: optional_pubkey(mint.authority.into()),
+                auto_approve_new_accounts: mint.auto_approve_new_accounts.into(),
+                auditor_elgamal_pubkey: Option::<ElGamalPubkey>::from(mint.auditor_elgamal_pubkey)
+                    .as_ref()
+                    .map(ElGamalPubkey::to_string),
+            }
+        }
+        ExtensionType::ConfidentialTransferAccount => {
+            let account = state
+                .get_extension::<ConfidentialTransferAccount>()
+                .map_err(decode_err)?;
+            TokenExtension::ConfidentialTransferAccount {
+                approved: account.approved.into(),
+                elgamal_pubkey: account.elgamal_pubkey.to_string(),
+                allow_confidential_credits: account.allow_confidential_credits.into(),
+                allow_non_confidential_credits: account.allow_non_confidential_credits.into(),
+                pending_balance_credit_counter: account.pending_balance_credit_counter.into(),
+                maximum_pending_balance_credit_counter: account
+                    .maximum_pending_balance_credit_counter
+                    .into(),
+            }
+        }
+        ExtensionType::ConfidentialTransferFeeConfig => {
+            let config = state
+                .get_extension::<ConfidentialTransferFeeConfig>()
+                .map_err(decode_err)?;
+            TokenExtension::ConfidentialTransferFeeConfig {
+                authority: optional_pubkey(config.authority.into()),
+                withdraw_withheld_authority_elgamal_pubkey: config
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .to_string(),
+                harvest_to_mint_enabled: config.harvest_to_mint_enabled.into(),
+            }
+        }
+        ExtensionType::ConfidentialTransferFeeAmount => {
+            TokenExtension::ConfidentialTransferFeeAmount
+        }
+        ExtensionType::DefaultAccountState => {
+            let default = state
+                .get_extension::<DefaultAccountState>()
+                .map_err(decode_err)?;
+            TokenExtension::DefaultAccountState {
+                state: account_state_name(default.state),
+            }
+        }
+        ExtensionType::ImmutableOwner => TokenExtension::ImmutableOwner,
+        ExtensionType::MemoTransfer => {
+            let memo = state.get_extension::<MemoTransfer>().map_err(decode_err)?;
+            TokenExtension::MemoTransfer {
+                require_incoming_transfer_memos: memo.require_incoming_transfer_memos.into(),
+            }
+        }
+        ExtensionType::NonTransferable => TokenExtension::NonTransferable,
+        ExtensionType::NonTransferableAccount => TokenExtension::NonTransferableAccount,
+        ExtensionType::InterestBearingConfig => {
+            let config = state
+                .get_extension::<InterestBearingConfig>()
+                .map_err(decode_err)?;
+            TokenExtension::InterestBearingConfig {
+                rate_authority: optional_pubkey(config.rate_authority.into()),
+                current_rate: config.current_rate.into(),
+                pre_update_average_rate: config.pre_update_average_rate.into(),
+                initialization_timestamp: config.initialization_timestamp.into(),
+                last_update_timestamp: config.last_update_timestamp.into(),
+            }
+        }
+        ExtensionType::CpiGuard => {
+            let guard = state.get_extension::<CpiGuard>().map_err(decode_err)?;
+            TokenExtension::CpiGuard {
+                lock_cpi: guard.lock_cpi.into(),
+            }
+        }
+        ExtensionType::PermanentDelegate => {
+            let delegate = state
+                .get_extension::<PermanentDelegate>()
+                .map_err(decode_err)?;
+            TokenExtension::PermanentDelegate {
+                delegate: optional_pubkey(delegate.delegate.into()),
+            }
+        }
+        ExtensionType::TransferHook => {
+            let hook = state.get_extension::<TransferHook>().map_err(decode_err)?;
+            TokenExtension::TransferHook {
+                authority: optional_pubkey(hook.authority.into()),
+                program_id: optional_pubkey(hook.program_id.into()),
+            }
+        }
+        ExtensionType::TransferHookAccount => {
+            let hook = state
+                .get_extension::<TransferHookAccount>()
+                .map_err(decode_err)?;
+            TokenExtension::TransferHookAccount {
+                transferring: hook.transferring.into(),
+            }
+        }
+        ExtensionType::MetadataPointer => {
+            let pointer = state
+                .get_extension::<MetadataPointer>()
+                .map_err(decode_err)?;
+            TokenExtension::MetadataPointer {
+                authority: optional_pubkey(pointer.authority.into()),
+                metadata_address: optional_pubkey(pointer.metadata_address.into()),
+            }
+        }
+        ExtensionType::TokenMetadata => {
+            let metadata = state
+                .get_variable_len_extension::<TokenMetadata>()
+                .map_err(decode_err)?;
+            TokenExtension::TokenMetadata {
+                update_authority: optional_pubkey(metadata.update_authority.into()),
+                mint: metadata.mint.to_string(),
+                name: metadata.name,
+                symbol: metadata.symbol,
+                uri: metadata.uri,
+                additional_metadata: metadata.additional_metadata,
+            }
+        }
+        ExtensionType::GroupPointer => {
+            let pointer = state.get_extension::<GroupPointer>().map_err(decode_err)?;
+            TokenExtension::GroupPointer {
+                authority: optional_pubkey(pointer.authority.into()),
+                group_address: optional_pubkey(pointer.group_address.into()),
+            }
+        }
+        ExtensionType::TokenGroup => {
+            let group = state.get_extension::<TokenGroup>().map_err(decode_err)?;
+            TokenExtension::TokenGroup {
+                update_authority: optional_pubkey(group.update_authority.into()),
+                mint: group.mint.to_string(),
+                size: group.size.into(),
+                max_size: group.max_size.into(),
+            }
+        }
+        ExtensionType::GroupMemberPointer => {
+            let pointer = state
+                .get_extension::<GroupMemberPointer>()
+                .map_err(decode_err)?;
+            TokenExtension::GroupMemberPointer {
+                authority: optional_pubkey(pointer.authority.into()),
+                member_address: optional_pubkey(pointer.member_address.into()),
+            }
+        }
+        ExtensionType::TokenGroupMember => {
+            let member = state
+                .get_extension::<TokenGroupMember>()
+                .map_err(decode_err)?;
+            TokenExtension::TokenGroupMember {
+                mint: member.mint.to_string(),
+                group: member.group.to_string(),
+                member_number: member.member_number.into(),
+            }
+        }
+        other => TokenExtension::Unknown {
+            extension_type: format!("{other:?}"),
+        },
+    };
+
+    Ok(extension)
+}
+
+impl TokenExtension {
+    pub fn name(&self) -> Cow<'static, str> {
+        let name = match self {
+            TokenExtension::TransferFeeConfig { .. } => "Transfer Fee Config",
+            TokenExtension::TransferFeeAmount { .. } => "Transfer Fee Amount",
+            TokenExtension::MintCloseAuthority { .. } => "Mint Close Authority",
+            TokenExtension::ConfidentialTransferMint { .. } => "Confidential Transfers",
+            TokenExtension::ConfidentialTransferAccount { .. } => "Confidential Transfers",
+            TokenExtension::ConfidentialTransferFeeConfig { .. } => "Confidential Transfer Fees",
+            TokenExtension::ConfidentialTransferFeeAmount => "Confidential Transfer Fee Amount",
+            TokenExtension::DefaultAccountState { .. } => "Default Account State",
+            TokenExtension::ImmutableOwner => "Immutable Owner",
+            TokenExtension::MemoTransfer { .. } => "Memo Transfer",
+            TokenExtension::NonTransferable => "Non-Transferable",
+            TokenExtension::NonTransferableAccount => "Non-Transferable Account",
+            TokenExtension::InterestBearingConfig { .. } => "Interest Bearing",
+            TokenExtension::CpiGuard { .. } => "CPI Guard",
+            TokenExtension::PermanentDelegate { .. } => "Permanent Delegate",
+            TokenExtension::TransferHook { .. } => "Transfer Hook",
+            TokenExtension::TransferHookAccount { .. } => "Transfer Hook Account",
+            TokenExtension::MetadataPointer { .. } => "Metadata Pointer",
+            TokenExtension::TokenMetadata { .. } => "Token Metadata",
+            TokenExtension::GroupPointer { .. } => "Group Pointer",
+            TokenExtension::TokenGroup { .. } => "Token Group",
+            TokenExtension::GroupMemberPointer { .. } => "Group Member Pointer",
+            TokenExtension::TokenGroupMember { .. } => "Token Group Member",
+            TokenExtension::Unknown { extension_type } => {
+                return Cow::Owned(extension_type.clone())
+            }
+        };
+        Cow::Borrowed(name)
+    }
+
+    /// Labelled fields to display. Token amounts are shown with the mint's
+    /// decimals.
+    pub fn fields(&self, decimals: u8) -> Vec<(&'static str, String)> {
+        let amount = |raw: &str| {
+            raw.parse()
+                .map(|raw| display_balance(raw, decimals as usize))
+                .unwrap_or_else(|_| raw.to_string())
+        };
+        let fee = |fee: &TransferFeeView| {
+            format!(
+                "{} (max {})",
+                format_basis_points(fee.transfer_fee_basis_points as i64),
+                amount(&fee.maximum_fee)
+            )
+        };
+
+        match self {
+            TokenExtension::TransferFeeConfig {
+                transfer_fee_config_authority,
+                withdraw_withheld_authority,
+                withheld_amount,
+                current_fee,
+                next_fee,
+            } => {
+                let mut fields = vec![("Current Fee", fee(current_fee))];
+                if let Some(next_fee) = next_fee {
+                    fields.push((
+                        "Next Fee",
+                        format!("{} from epoch {}", fee(next_fee), next_fee.epoch),
+                    ));
+                }
+                fields.extend([
+                    ("Withheld Amount", amount(withheld_amount)),
+                    ("Config Authority", format_authority(transfer_fee_config_authority)),
+                    ("Withdraw Authority", format_authority(withdraw_withheld_authority)),
+                ]);
+                fields
+            }
+            TokenExtension::TransferFeeAmount { withheld_amount } => {
+                vec![("Withheld Amount", amount(withheld_amount))]
+            }
+            TokenExtension::MintCloseAuthority { close_authority } => {
+                vec![("Close Authority", format_authority(close_authority))]
+            }
+            TokenExtension::ConfidentialTransferMint {
+                authority,
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            } => vec![
+                ("Authority", format_authority(authority)),
+                ("Auto Approve", auto_approve_new_accounts.to_string()),
+                ("Auditor", format_authority(auditor_elgamal_pubkey)),
+            ],
+            TokenExtension::ConfidentialTransferAccount {
+                approved,
+                elgamal_pubkey,
+                allow_confidential_credits,
+                allow_non_confidential_credits,
+                pending_balance_credit_counter,
+                maximum_pending_balance_credit_counter,
+            } => vec![
+                ("Approved", approved.to_string()),
+                ("ElGamal Pubkey", elgamal_pubkey.clone()),
+                ("Confidential Credits", allow_confidential_credits.to_string()),
+                ("Non-Confidential Credits", allow_non_confidential_credits.to_string()),
+                (
+                    "Pending Credits",
+                    format!(
+                        "{pending_balance_credit_counter} / {maximum_pending_balance_credit_counter}"
+                    ),
+                ),
+            ],
+            TokenExtension::ConfidentialTransferFeeConfig {
+                authority,
+                withdraw_withheld_authority_elgamal_pubkey,
+                harvest_to_mint_enabled,
+            } => vec![
+                ("Authority", format_authority(authority)),
+                (
+                    "Withdraw ElGamal Pubkey",
+                    withdraw_withheld_authority_elgamal_pubkey.clone(),
+                ),
+                ("Harvest To Mint", harvest_to_mint_enabled.to_string()),
+            ],
+            TokenExtension::DefaultAccountState { state } => vec![("State", state.to_string())],
+            TokenExtension::MemoTransfer {
+                require_incoming_transfer_memos,
+            } => vec![(
+                "Memo Required",
+                require_incoming_transfer_memos.to_string(),
+            )],
+            TokenExtension::InterestBearingConfig {
+                rate_authority,
+                current_rate,
+                pre_update_average_rate,
+                initialization_timestamp,
+                last_update_timestamp,
+            } => vec![
+                ("Current Rate", format_basis_points(*current_rate as i64)),
+                (
+                    "Previous Average Rate",
+                    format_basis_points(*pre_update_average_rate as i64),
+                ),
+                ("Initialized At", initialization_timestamp.to_string()),
+                ("Last Updated At", last_update_timestamp.to_string()),
+                ("Rate Authority", format_authority(rate_authority)),
+            ],
+            TokenExtension::CpiGuard { lock_cpi } => vec![("CPI Locked", lock_cpi.to_string())],
+            TokenExtension::PermanentDelegate { delegate } => {
+                vec![("Delegate", format_authority(delegate))]
+            }
+            TokenExtension::TransferHook {
+                authority,
+                program_id,
+            } => vec![
+                ("Program", format_authority(program_id)),
+                ("Authority", format_authority(authority)),
+            ],
+            TokenExtension::TransferHookAccount { transferring } => {
+                vec![("Transferring", transferring.to_string())]
+            }
+            TokenExtension::MetadataPointer {
+                authority,
+                metadata_address,
+            } => vec![
+                ("Address", format_authority(metadata_address)),
+                ("Authority", format_authority(authority)),
+            ],
+            TokenExtension::TokenMetadata {
+                update_authority,
+                mint,
+                name,
+                symbol,
+                uri,
+                additional_metadata,
+            } => {
+                let mut fields = vec![
+                    ("Name", name.clone()),
+                    ("Symbol", symbol.clone()),
+                    ("URI", uri.clone()),
+                    ("Mint", mint.clone()),
+                    ("Update Authority", format_authority(update_authority)),
+                ];
+                fields.extend(
+                    additional_metadata
+                        .iter()
+                        .map(|(key, value)| ("Additional", format!("{key}: {value}"))),
+                );
+                fields
+            }
+            TokenExtension::GroupPointer {
+                authority,
+                group_address,
+            } => vec![
+                ("Address", format_authority(group_address)),
+                ("Authority", format_authority(authority)),
+            ],
+            TokenExtension::TokenGroup {
+                update_authority,
+                mint,
+                size,
+                max_size,
+            } => vec![
+                ("Mint", mint.clone()),
+                ("Size", format!("{size} / {max_size}")),
+                ("Update Authority", format_authority(update_authority)),
+            ],
+            TokenExtension::GroupMemberPointer {
+                authority,
+                member_address,
+            } => vec![
+                ("Address", format_authority(member_address)),
+                ("Authority", format_authority(authority)),
+            ],
+            TokenExtension::TokenGroupMember {
+                mint,
+                group,
+                member_number,
+            } => vec![
+                ("Mint", mint.clone()),
+                ("Group", group.clone()),
+                ("Member Number", member_number.to_string()),
+            ],
+            TokenExtension::ConfidentialTransferFeeAmount
+            | TokenExtension::ImmutableOwner
+            | TokenExtension::NonTransferable
+            | TokenExtension::NonTransferableAccount
+            | TokenExtension::Unknown { .. } => vec![],
+        }
+    }
+}
+
+/// A table of every extension and its fields, or `None` if there are none
+pub fn extensions_table(extensions: &[TokenExtension], decimals: u8) -> Option<Table> {
+    if extensions.is_empty() {
+        return None;
+    }
+
+    let mut table = Table::new();
+    table.set_titles(row![c->"Extensions", ""]);
+    for extension in extensions {
+        let fields = extension
+            .fields(decimals)
+            .into_iter()
+            .map(|(label, value)| format!("{label}: {value}"))
+            .collect::<Vec<_>>();
+        let fields = if fields.is_empty() {
+            "Enabled".to_string()
+        } else {
+            fields.join("\n")
+        };
+        table.add_row(row![c->extension.name(), fields]);
+    }
+
+    Some(table)
+}
+
+fn optional_pubkey(pubkey: Option<Pubkey>) -> Option<String> {
+    pubkey.as_ref().map(ToString::to_string)
+}
+
+fn format_authority(authority: &Option<String>) -> String {
+    authority.clone().unwrap_or("None".to_string())
+}
+
+/// Names the `AccountState` stored as a byte by the default account state
+/// extension
+fn account_state_name(state: u8) -> &'static str {
+    match state {
+        0 => "Uninitialized",
+        1 => "Initialized",
+        2 => "Frozen",
+        _ => "Unknown",
+    }
+}
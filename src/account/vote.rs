@@ -0,0 +1,187 @@
+//! Parsing vote accounts
+
+use num_format::{Locale, ToFormattedString};
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
+use solana_sdk::{
+    account::Account,
+    clock::{Epoch, Slot},
+    pubkey::Pubkey,
+    vote::{self, state::VoteState},
+};
+
+use crate::{
+    error::{ExplorerError, Result},
+    utils::display_balance,
+};
+
+use super::ParsedAccount;
+
+pub struct VoteAccount {
+    pub lamports: u64,
+    pub state: VoteState,
+}
+
+impl VoteAccount {
+    pub fn parse(account: &Account) -> Result<Option<ParsedAccount<'_>>> {
+        if account.owner != vote::program::ID {
+            return Ok(None);
+        }
+
+        // Converts all prior vote state versions to the current one
+        let state = VoteState::deserialize(&account.data)
+            .map_err(|e| ExplorerError::Decode(format!("vote account: {e}")))?;
+
+        Ok(Some(ParsedAccount::Vote(Box::new(VoteAccount {
+            lamports: account.lamports,
+            state,
+        }))))
+    }
+
+    pub fn display(self, key: &Pubkey) {
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+        let padded_width = width.saturating_sub(4);
+
+        let state = &self.state;
+
+        let mut vote_table = Table::new();
+        vote_table.set_titles(row![c->"Vote Account", key]);
+        vote_table.add_row(row![c->"SOL Balance", display_balance(self.lamports, 9)]);
+        vote_table.add_row(row![c->"Node Identity", state.node_pubkey]);
+        for (epoch, voter) in state.authorized_voters().iter() {
+            vote_table.add_row(row![
+                c->"Authorized Voter",
+                format!("{voter} (from epoch {epoch})")
+            ]);
+        }
+        vote_table.add_row(row![c->"Authorized Withdrawer", state.authorized_withdrawer]);
+        vote_table.add_row(row![c->"Commission", format!("{}%", state.commission)]);
+        vote_table.add_row(row![
+            c->"Root Slot",
+            state
+                .root_slot
+                .map(|slot| slot.to_string())
+                .unwrap_or("None".to_string())
+        ]);
+        vote_table.add_row(row![
+            c->"Last Timestamp",
+            format!(
+                "{} (slot {})",
+                state.last_timestamp.timestamp, state.last_timestamp.slot
+            )
+        ]);
+
+        // Most recent votes first
+        let mut votes_table = Table::new();
+        votes_table.set_titles(row![c->"Voted Slot", c->"Confirmations", c->"Latency"]);
+        for vote in state.votes.iter().rev() {
+            votes_table.add_row(row![
+                r->vote.slot(),
+                r->vote.confirmation_count(),
+                r->vote.latency
+            ]);
+        }
+
+        // Most recent epochs first
+        let mut credits_table = Table::new();
+        credits_table.set_titles(row![c->"Epoch", c->"Credits", c->"Earned"]);
+        for &(epoch, credits, prev_credits) in state.epoch_credits.iter().rev() {
+            credits_table.add_row(row![
+                r->epoch,
+                r->credits.to_formatted_string(&Locale::en),
+                r->credits.saturating_sub(prev_credits).to_formatted_string(&Locale::en)
+            ]);
+        }
+
+        let mut tables = Table::new();
+        tables.add_row(row![c->vote_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+        if !state.votes.is_empty() {
+            tables.add_row(row![c->votes_table]);
+            tables.add_row(row![" ".repeat(padded_width)]);
+        }
+        if !state.epoch_credits.is_empty() {
+            tables.add_row(row![c->credits_table]);
+            tables.add_row(row![" ".repeat(padded_width)]);
+        }
+        tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+        tables.printstd();
+    }
+
+    pub fn to_view(&self) -> VoteAccountView {
+        let state = &self.state;
+        VoteAccountView {
+            node_identity: state.node_pubkey.to_string(),
+            authorized_voters: state
+                .authorized_voters()
+                .iter()
+                .map(|(&epoch, voter)| AuthorizedVoterView {
+                    epoch,
+                    voter: voter.to_string(),
+                })
+                .collect(),
+            authorized_withdrawer: state.authorized_withdrawer.to_string(),
+            commission: state.commission,
+            root_slot: state.root_slot,
+            last_timestamp: state.last_timestamp.timestamp,
+            last_timestamp_slot: state.last_timestamp.slot,
+            votes: state
+                .votes
+                .iter()
+                .map(|vote| VoteView {
+                    slot: vote.slot(),
+                    confirmation_count: vote.confirmation_count(),
+                    latency: vote.latency,
+                })
+                .collect(),
+            epoch_credits: state
+                .epoch_credits
+                .iter()
+                .map(|&(epoch, credits, prev_credits)| EpochCreditsView {
+                    epoch,
+                    credits,
+                    prev_credits,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Serializable view model of a [`VoteAccount`]
+#[derive(Serialize)]
+pub struct VoteAccountView {
+    pub node_identity: String,
+    pub authorized_voters: Vec<AuthorizedVoterView>,
+    pub authorized_withdrawer: String,
+    pub commission: u8,
+    pub root_slot: Option<Slot>,
+    pub last_timestamp: i64,
+    pub last_timestamp_slot: Slot,
+    /// Oldest vote first
+    pub votes: Vec<VoteView>,
+    /// Oldest epoch first
+    pub epoch_credits: Vec<EpochCreditsView>,
+}
+
+#[derive(Serialize)]
+pub struct AuthorizedVoterView {
+    /// Epoch from which this voter is authorized
+    pub epoch: Epoch,
+    pub voter: String,
+}
+
+#[derive(Serialize)]
+pub struct VoteView {
+    pub slot: Slot,
+    pub confirmation_count: u32,
+    pub latency: u8,
+}
+
+#[derive(Serialize)]
+pub struct EpochCreditsView {
+    pub epoch: Epoch,
+    pub credits: u64,
+    pub prev_credits: u64,
+}
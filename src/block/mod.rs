@@ -2,122 +2,205 @@ use std::{cmp::Reverse, collections::BTreeMap};
 
 use num_format::{Locale, ToFormattedString};
 use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
 use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcBlockConfig};
 use solana_sdk::pubkey::Pubkey;
 use solana_transaction_status::{
     RewardType, TransactionDetails, UiConfirmedBlock, UiTransactionEncoding,
 };
 
-use crate::utils::get_network;
-
-pub async fn handler(rpc_url: String, block: crate::Block) {
-    // Build RPC Client
-    let client = RpcClient::new(get_network(&rpc_url));
-
-    'slots: for slot in block.start..=block.end.unwrap_or(block.start) {
-        for attempt in 1..=5 {
-            // Fetch block
-            const BLOCK_CONFIG: RpcBlockConfig = RpcBlockConfig {
-                encoding: Some(UiTransactionEncoding::Base64),
-                transaction_details: Some(TransactionDetails::Full),
-                rewards: Some(true),
-                commitment: None,
-                max_supported_transaction_version: Some(0),
-            };
-            let Ok(fetched_block) = client.get_block_with_config(slot, BLOCK_CONFIG).await else {
-                println!("failed to fetch block, attempt {attempt}/5");
+use crate::{
+    config::confirmed_commitment,
+    error::{ExplorerError, Result},
+    utils::{print_json, OutputFormat},
+};
+
+pub async fn handler(client: RpcClient, output: OutputFormat, block: crate::Block) -> Result<()> {
+    let end = block.end.unwrap_or(block.start);
+    for slot in block.start..=end {
+        let fetched_block = match fetch_block(&client, slot).await {
+            Ok(fetched_block) => fetched_block,
+            // Skipped or pruned slots are expected within a range
+            Err(ExplorerError::NotFound(e)) if end > block.start => {
+                eprintln!("skipping slot {slot}: {e}");
                 continue;
-            };
-
-            let parsed_block = ParsedBlock::new(&fetched_block);
-
-            println!();
-
-            use terminal_size::{terminal_size, Width};
-            let size = terminal_size();
-            let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
-            let padded_width = width.saturating_sub(4);
-
-            let mut program_map = BTreeMap::new();
-            let transactions = fetched_block.transactions.unwrap();
-            let mut vote = 0;
-            let mut nonvote = 0;
-            let compute_units: u64 = transactions
-                .iter()
-                .map(|tx| {
-                    let decoded_tx = tx.transaction.decode().unwrap();
-                    let ixs = decoded_tx.message.instructions();
-                    if ixs.len() == 1
-                        && *ixs[0].program_id(decoded_tx.message.static_account_keys())
-                            == solana_sdk::vote::program::ID
-                    {
-                        vote += 1;
-                    } else {
-                        nonvote += 1;
-                    }
-
-                    for ix in ixs {
-                        program_map
-                            .entry(
-                                ix.program_id(decoded_tx.message.static_account_keys())
-                                    .clone(),
-                            )
-                            .and_modify(|c: &mut u64| {
-                                *c += 1;
-                            })
-                            .or_insert(1);
-                    }
-
-                    Option::<u64>::from(tx.meta.clone().unwrap().compute_units_consumed).unwrap()
-                })
-                .sum();
-
-            let mut table_of_tables = Table::new();
-
-            // Header table
-            let mut header_table = Table::new();
-            header_table.add_row(row![c->"Slot", slot]);
-            header_table.add_row(row![c->"Parent Slot", fetched_block.parent_slot]);
-            header_table.add_row(row![c->"Leader", &parsed_block.leader]);
-            header_table.add_row(row![c->"Rewards", format!("◎{}.{:09}", parsed_block.rewards, parsed_block.rewards_sub)]);
-            header_table.add_row(row![c->"Blockhash", &fetched_block.blockhash]);
-            header_table.add_row(
-                row![c->"Transactions", format!("{} nonvote + {} vote = {} total", nonvote, vote, transactions.len())],
-            );
-            header_table
-                .add_row(row![c->"Compute Units", compute_units.to_formatted_string(&Locale::en)]);
-            table_of_tables.add_row(row![c->header_table]);
-
-            // Program table
-            if block.verbose {
-                let mut program_table = Table::new();
-
-                let mut program_invocations: Vec<(Pubkey, u64)> =
-                    program_map.into_iter().map(|kv| kv).collect();
-                program_invocations.sort_by_key(|kv| Reverse(kv.1));
-
-                program_table.add_row(row!["Program", "Top Level Invocations"]);
-                for (program, invocations) in program_invocations {
-                    program_table
-                        .add_row(row![program, invocations.to_formatted_string(&Locale::en)]);
+            }
+            Err(e) => return Err(e),
+        };
+
+        let parsed_block = ParsedBlock::new(&fetched_block);
+
+        let mut program_map = BTreeMap::new();
+        let transactions = fetched_block.transactions.unwrap_or_default();
+        let mut vote = 0;
+        let mut nonvote = 0;
+        let compute_units: u64 = transactions
+            .iter()
+            .map(|tx| {
+                let Some(decoded_tx) = tx.transaction.decode() else {
+                    return 0;
+                };
+                let ixs = decoded_tx.message.instructions();
+                if ixs.len() == 1
+                    && *ixs[0].program_id(decoded_tx.message.static_account_keys())
+                        == solana_sdk::vote::program::ID
+                {
+                    vote += 1;
+                } else {
+                    nonvote += 1;
                 }
 
-                table_of_tables.add_row(row![" ".repeat(padded_width)]);
-                table_of_tables.add_row(row![c->program_table]);
+                for ix in ixs {
+                    program_map
+                        .entry(*ix.program_id(decoded_tx.message.static_account_keys()))
+                        .and_modify(|c: &mut u64| {
+                            *c += 1;
+                        })
+                        .or_insert(1);
+                }
+
+                tx.meta
+                    .as_ref()
+                    .and_then(|meta| meta.compute_units_consumed.clone().into())
+                    .unwrap_or_default()
+            })
+            .sum();
+
+        let mut program_invocations: Vec<(Pubkey, u64)> = program_map.into_iter().collect();
+        program_invocations.sort_by_key(|kv| Reverse(kv.1));
+
+        if output == OutputFormat::Json {
+            print_json(&BlockView {
+                slot,
+                parent_slot: fetched_block.parent_slot,
+                leader: parsed_block.leader,
+                rewards: parsed_block.rewards * 1_000_000_000 + parsed_block.rewards_sub,
+                blockhash: fetched_block.blockhash,
+                transactions: BlockTransactionsView {
+                    vote,
+                    nonvote,
+                    total: transactions.len(),
+                },
+                compute_units,
+                program_invocations: block.verbose.then(|| {
+                    program_invocations
+                        .into_iter()
+                        .map(|(program, invocations)| ProgramInvocationsView {
+                            program: program.to_string(),
+                            invocations,
+                        })
+                        .collect()
+                }),
+            });
+            continue;
+        }
+
+        println!();
+
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+        let padded_width = width.saturating_sub(4);
+
+        let mut table_of_tables = Table::new();
+
+        // Header table
+        let mut header_table = Table::new();
+        header_table.add_row(row![c->"Slot", slot]);
+        header_table.add_row(row![c->"Parent Slot", fetched_block.parent_slot]);
+        header_table.add_row(row![c->"Leader", &parsed_block.leader]);
+        header_table.add_row(row![c->"Rewards", format!("◎{}.{:09}", parsed_block.rewards, parsed_block.rewards_sub)]);
+        header_table.add_row(row![c->"Blockhash", &fetched_block.blockhash]);
+        header_table.add_row(
+            row![c->"Transactions", format!("{} nonvote + {} vote = {} total", nonvote, vote, transactions.len())],
+        );
+        header_table
+            .add_row(row![c->"Compute Units", compute_units.to_formatted_string(&Locale::en)]);
+        table_of_tables.add_row(row![c->header_table]);
+
+        // Program table
+        if block.verbose {
+            let mut program_table = Table::new();
+
+            program_table.add_row(row!["Program", "Top Level Invocations"]);
+            for (program, invocations) in program_invocations {
+                program_table.add_row(row![program, invocations.to_formatted_string(&Locale::en)]);
             }
 
-            table_of_tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
             table_of_tables.add_row(row![" ".repeat(padded_width)]);
+            table_of_tables.add_row(row![c->program_table]);
+        }
+
+        table_of_tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table_of_tables.add_row(row![" ".repeat(padded_width)]);
 
-            table_of_tables.printstd();
+        table_of_tables.printstd();
 
-            println!();
-            continue 'slots;
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Fetches a block, retrying on rpc failures. Skipped or unavailable slots
+/// are not retried.
+async fn fetch_block(client: &RpcClient, slot: u64) -> Result<UiConfirmedBlock> {
+    const ATTEMPTS: usize = 5;
+    let config = RpcBlockConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        transaction_details: Some(TransactionDetails::Full),
+        rewards: Some(true),
+        commitment: Some(confirmed_commitment(client)),
+        max_supported_transaction_version: Some(0),
+    };
+
+    let mut attempt = 1;
+    loop {
+        match client
+            .get_block_with_config(slot, config)
+            .await
+            .map_err(ExplorerError::from)
+        {
+            Ok(fetched_block) => return Ok(fetched_block),
+            Err(ExplorerError::Rpc(e)) if attempt < ATTEMPTS => {
+                eprintln!("failed to fetch block {slot}, attempt {attempt}/{ATTEMPTS}: {e}");
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
         }
-        println!("failed to fetch block");
     }
 }
 
+/// Serializable view model of a block and its [`ParsedBlock`] summary.
+///
+/// `rewards` is the leader's fee reward in lamports.
+#[derive(Serialize)]
+pub struct BlockView {
+    pub slot: u64,
+    pub parent_slot: u64,
+    pub leader: String,
+    pub rewards: i64,
+    pub blockhash: String,
+    pub transactions: BlockTransactionsView,
+    pub compute_units: u64,
+    /// Top level invocations per program, only present in verbose mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program_invocations: Option<Vec<ProgramInvocationsView>>,
+}
+
+#[derive(Serialize)]
+pub struct BlockTransactionsView {
+    pub vote: u64,
+    pub nonvote: u64,
+    pub total: usize,
+}
+
+#[derive(Serialize)]
+pub struct ProgramInvocationsView {
+    pub program: String,
+    pub invocations: u64,
+}
+
 pub struct ParsedBlock {
     pub leader: String,
     pub rewards: i64,
@@ -125,21 +208,22 @@ pub struct ParsedBlock {
 }
 
 impl ParsedBlock {
-    /// This makes assumptions about the rpc request config that allow for unwraps
-    /// (in the absence of malicious rpc)
+    /// The leader is taken from the fee reward, which is missing for blocks
+    /// without fees (e.g. empty blocks)
     pub fn new(fetched_block: &UiConfirmedBlock) -> ParsedBlock {
-        let rewards = fetched_block
+        let fee_reward = fetched_block
             .rewards
-            .as_ref()
-            .unwrap()
             .iter()
-            .find(|reward| reward.reward_type == Some(RewardType::Fee))
-            .unwrap();
+            .flatten()
+            .find(|reward| reward.reward_type == Some(RewardType::Fee));
 
+        let lamports = fee_reward.map(|reward| reward.lamports).unwrap_or_default();
         ParsedBlock {
-            leader: rewards.pubkey.clone(),
-            rewards: rewards.lamports / 1_000_000_000,
-            rewards_sub: rewards.lamports % 1_000_000_000,
+            leader: fee_reward
+                .map(|reward| reward.pubkey.clone())
+                .unwrap_or("Unknown".to_string()),
+            rewards: lamports / 1_000_000_000,
+            rewards_sub: lamports % 1_000_000_000,
         }
     }
 }
@@ -0,0 +1,104 @@
+//! Crate-wide error type
+
+use std::fmt::{self, Display};
+
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_custom_error::{
+        JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP, JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+        JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED,
+        JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
+        JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION,
+    },
+    rpc_request::RpcError,
+};
+
+pub type Result<T> = std::result::Result<T, ExplorerError>;
+
+#[derive(Debug)]
+pub enum ExplorerError {
+    /// An rpc request failed, e.g. due to the network, rate limits or an
+    /// unhealthy node
+    Rpc(Box<ClientError>),
+
+    /// The requested account, transaction or block does not exist, or has
+    /// been pruned by the rpc node
+    NotFound(String),
+
+    /// Data returned by the rpc could not be decoded
+    Decode(String),
+
+    /// The transaction (or data) version is not supported by the explorer or
+    /// the rpc node
+    UnsupportedVersion(String),
+
+    /// Invalid configuration or user supplied input files
+    Config(String),
+
+    /// Failed to read or write a local file
+    Io(std::io::Error),
+}
+
+impl ExplorerError {
+    /// Process exit code for each error class
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ExplorerError::Rpc(_) => 3,
+            ExplorerError::NotFound(_) => 4,
+            ExplorerError::Decode(_) => 5,
+            ExplorerError::UnsupportedVersion(_) => 6,
+            ExplorerError::Config(_) => 7,
+            ExplorerError::Io(_) => 8,
+        }
+    }
+}
+
+impl Display for ExplorerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExplorerError::Rpc(e) => {
+                write!(f, "rpc request failed: {e}")?;
+                if e.to_string().contains("429") {
+                    write!(f, "\nhint: the rpc node is rate limiting requests. Consider using a private rpc endpoint (see `sol config`)")?;
+                }
+                Ok(())
+            }
+            ExplorerError::NotFound(e) => write!(f, "{e}"),
+            ExplorerError::Decode(e) => write!(f, "failed to decode {e}"),
+            ExplorerError::UnsupportedVersion(e) => write!(f, "unsupported version: {e}"),
+            ExplorerError::Config(e) => write!(f, "{e}"),
+            ExplorerError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ExplorerError {}
+
+impl From<ClientError> for ExplorerError {
+    fn from(error: ClientError) -> ExplorerError {
+        if let ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. }) =
+            error.kind()
+        {
+            match *code {
+                JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP
+                | JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE
+                | JSON_RPC_SERVER_ERROR_SLOT_SKIPPED
+                | JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED
+                | JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE => {
+                    return ExplorerError::NotFound(message.clone())
+                }
+                JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION => {
+                    return ExplorerError::UnsupportedVersion(message.clone())
+                }
+                _ => {}
+            }
+        }
+        ExplorerError::Rpc(Box::new(error))
+    }
+}
+
+impl From<std::io::Error> for ExplorerError {
+    fn from(error: std::io::Error) -> ExplorerError {
+        ExplorerError::Io(error)
+    }
+}
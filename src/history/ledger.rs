@@ -0,0 +1,445 @@
+//! Exporting an address' history as a ledger of its balance changes
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, SecondsFormat};
+use clap::ValueEnum;
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient as Client, rpc_request::TokenAccountsFilter,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey, signature::Signature};
+
+use super::{fetch_signatures, ResolvedTransaction, CONCURRENT_FETCHES};
+use crate::{
+    error::{ExplorerError, Result},
+    transaction::{
+        fetch_transaction,
+        token_balances::{resolve_mint_symbols, token_balance_changes},
+    },
+    utils::{print_json, OutputFormat},
+    History,
+};
+
+/// File formats a ledger can be exported as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LedgerFormat {
+    /// Comma separated values, with a header row
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl LedgerFormat {
+    /// `.jsonl` files are written as JSON lines, anything else as csv
+    fn from_path(path: &Path) -> LedgerFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("jsonl") => LedgerFormat::Jsonl,
+            _ => LedgerFormat::Csv,
+        }
+    }
+}
+
+/// The change in one asset's balance in one transaction. Amounts are signed
+/// decimal strings in whole units (SOL, or tokens after applying decimals).
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerEntry {
+    /// Block time in RFC 3339, UTC
+    pub timestamp: Option<String>,
+    pub signature: String,
+    pub slot: u64,
+    pub success: bool,
+    /// `None` for SOL
+    pub mint: Option<String>,
+    pub symbol: Option<String>,
+    /// For SOL, this includes the fee
+    pub delta: String,
+    /// Fee paid by the address, in SOL. Only set on SOL entries.
+    pub fee: Option<String>,
+}
+
+pub async fn export(
+    client: &Client,
+    output: OutputFormat,
+    history: &History,
+    path: &Path,
+) -> Result<()> {
+    let address = &history.pubkey;
+    let format = history
+        .export_format
+        .unwrap_or_else(|| LedgerFormat::from_path(path));
+
+    // Without a limit, walk back to the address' first transaction
+    let signatures = ledger_signatures(
+        client,
+        address,
+        history.before,
+        history.until,
+        history.limit.unwrap_or(usize::MAX),
+    )
+    .await?;
+    eprintln!("Fetching {} transactions of {address}", signatures.len());
+
+    // A ledger with missing transactions would silently misstate balances, so
+    // any failure aborts the export
+    let mut entries: Vec<LedgerEntry> = stream::iter(&signatures)
+        .map(|status| ledger_entries(client, address, status))
+        .buffered(CONCURRENT_FETCHES)
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+    resolve_symbols(&mut entries, client).await;
+
+    write_ledger(path, format, &entries)?;
+
+    match output {
+        OutputFormat::Table => println!(
+            "Wrote {} ledger entries from {} transactions of {address} to {}",
+            entries.len(),
+            signatures.len(),
+            path.display()
+        ),
+        OutputFormat::Json => print_json(&ExportView {
+            address: address.to_string(),
+            path: path.display().to_string(),
+            transactions: signatures.len(),
+            entries: entries.len(),
+        }),
+    }
+
+    Ok(())
+}
+
+/// Signatures of the address and of the token accounts it currently owns,
+/// oldest first. Token transfers don't always include the owner (e.g. incoming
+/// transfers), so the token accounts' own histories are needed as well. Token
+/// accounts that have since been closed can't be found this way.
+async fn ledger_signatures(
+    client: &Client,
+    address: &Pubkey,
+    before: Option<Signature>,
+    until: Option<Signature>,
+    limit: usize,
+) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+    let mut token_accounts = vec![];
+    for program_id in [spl_token::ID, spl_token_2022::ID] {
+        for keyed in client
+            .get_token_accounts_by_owner(address, TokenAccountsFilter::ProgramId(program_id))
+            .await?
+        {
+            token_accounts.push(keyed.pubkey.parse::<Pubkey>().map_err(|e| {
+                ExplorerError::Decode(format!("token account {}: {e}", keyed.pubkey))
+            })?);
+        }
+    }
+
+    let histories: Vec<_> = stream::iter(std::iter::once(address).chain(&token_accounts))
+        .map(|account| fetch_signatures(client, account, before, until, limit))
+        .buffered(CONCURRENT_FETCHES)
+        .try_collect()
+        .await?;
+
+    // Each history is newest first, so after a stable sort by slot the newest
+    // `limit` transactions across all of them come first
+    let mut seen = HashSet::new();
+    let mut signatures: Vec<_> = histories
+        .into_iter()
+        .flatten()
+        .filter(|status| seen.insert(status.signature.clone()))
+        .collect();
+    signatures.sort_by_key(|status| Reverse(status.slot));
+    signatures.truncate(limit);
+    // Ledgers read oldest first
+    signatures.reverse();
+
+    Ok(signatures)
+}
+
+/// Serializable summary of an export
+#[derive(Serialize)]
+pub struct ExportView {
+    pub address: String,
+    pub path: String,
+    pub transactions: usize,
+    pub entries: usize,
+}
+
+/// Fetches a transaction and computes the address' SOL and token balance
+/// changes in it. Tokens are those in token accounts owned by the address,
+/// summed per mint, or the address itself if it is a token account.
+async fn ledger_entries(
+    client: &Client,
+    address: &Pubkey,
+    status: &RpcConfirmedTransactionStatusWithSignature,
+) -> Result<Vec<LedgerEntry>> {
+    let signature: Signature = status
+        .signature
+        .parse()
+        .map_err(|e| ExplorerError::Decode(format!("signature {}: {e}", status.signature)))?;
+    let transaction = fetch_transaction(client, &signature).await?;
+    let transaction = ResolvedTransaction::new(transaction, client).await?;
+    Ok(transaction_entries(
+        &transaction,
+        address,
+        &status.signature,
+    ))
+}
+
+/// The address' balance changes in a fetched transaction. Symbols of tokens
+/// are resolved later, for all transactions at once.
+fn transaction_entries(
+    transaction: &ResolvedTransaction,
+    address: &Pubkey,
+    signature: &str,
+) -> Vec<LedgerEntry> {
+    let entry = |mint: Option<String>, symbol: Option<String>, delta, fee| LedgerEntry {
+        timestamp: transaction
+            .block_time
+            .and_then(|time| DateTime::from_timestamp(time, 0))
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        signature: signature.to_string(),
+        slot: transaction.slot,
+        success: transaction.meta.err.is_none(),
+        mint,
+        symbol,
+        delta,
+        fee,
+    };
+    let mut entries = vec![];
+
+    let fee = if transaction.fee_payer() == *address {
+        transaction.meta.fee
+    } else {
+        0
+    };
+    let lamport_delta = transaction.lamport_delta(address).unwrap_or_default();
+    if lamport_delta != 0 || fee != 0 {
+        entries.push(entry(
+            None,
+            Some("SOL".to_string()),
+            format_amount(lamport_delta as i128, 9),
+            Some(format_amount(fee as i128, 9)),
+        ));
+    }
+
+    let accounts: Vec<AccountMeta> = transaction
+        .keys
+        .iter()
+        .map(|key| AccountMeta::new_readonly(*key, false))
+        .collect();
+    let owner = address.to_string();
+    let mut token_deltas: BTreeMap<String, (i128, u8)> = BTreeMap::new();
+    for change in token_balance_changes(&transaction.meta, &accounts) {
+        if change.account == *address || change.owner.as_deref() == Some(owner.as_str()) {
+            let (delta, _decimals) = token_deltas
+                .entry(change.mint.clone())
+                .or_insert((0, change.decimals));
+            *delta += change.delta();
+        }
+    }
+    for (mint, (delta, decimals)) in token_deltas {
+        if delta != 0 {
+            entries.push(entry(
+                Some(mint),
+                None,
+                format_amount(delta, decimals),
+                None,
+            ));
+        }
+    }
+
+    entries
+}
+
+/// Resolves symbols for all token entries, fetching each mint once
+async fn resolve_symbols(entries: &mut [LedgerEntry], client: &Client) {
+    let symbols = resolve_mint_symbols(
+        entries.iter().filter_map(|entry| entry.mint.as_deref()),
+        client,
+    )
+    .await;
+    for entry in entries {
+        if let Some(mint) = &entry.mint {
+            entry.symbol = symbols.get(mint).cloned().flatten();
+        }
+    }
+}
+
+/// Only called once every transaction was fetched, so failed fetches never
+/// leave a partial ledger behind
+fn write_ledger(path: &Path, format: LedgerFormat, entries: &[LedgerEntry]) -> Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    match format {
+        LedgerFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(file);
+            for entry in entries {
+                writer.serialize(entry).map_err(std::io::Error::from)?;
+            }
+            writer.flush()?;
+        }
+        LedgerFormat::Jsonl => {
+            for entry in entries {
+                serde_json::to_writer(&mut file, entry).map_err(std::io::Error::from)?;
+                writeln!(file)?;
+            }
+            file.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Formats a signed raw amount as a plain decimal, e.g. `-0.000005000`.
+/// Unlike `display_balance`, there are no thousands separators, so the
+/// result can be read back as a number.
+fn format_amount(amount: i128, decimals: u8) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    let digits = amount.unsigned_abs().to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return format!("{sign}{digits}");
+    }
+
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    format!("{sign}{whole}.{fraction}")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use solana_sdk::message::{Message, VersionedMessage};
+
+    use super::*;
+
+    #[test]
+    fn format_amount_is_plain_signed_decimal() {
+        assert_eq!(format_amount(-5000, 9), "-0.000005000");
+        assert_eq!(format_amount(1_234_567_890_123, 9), "1234.567890123");
+        assert_eq!(format_amount(-1_500_000, 6), "-1.500000");
+        assert_eq!(format_amount(7, 6), "0.000007");
+        assert_eq!(format_amount(0, 6), "0.000000");
+        assert_eq!(format_amount(-42, 0), "-42");
+        assert_eq!(format_amount(42, 0), "42");
+    }
+
+    /// A transaction between `wallet` (the fee payer) and another wallet, with
+    /// the given token balances of accounts 1 to 3
+    fn transaction(
+        wallet: &Pubkey,
+        pre_token_balances: serde_json::Value,
+        post_token_balances: serde_json::Value,
+    ) -> ResolvedTransaction {
+        let keys = vec![
+            *wallet,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let meta = serde_json::from_value(json!({
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [1_000_000_000u64, 2_039_280, 2_039_280, 2_039_280],
+            "postBalances": [1_002_034_280u64, 0, 2_039_280, 2_039_280],
+            "preTokenBalances": pre_token_balances,
+            "postTokenBalances": post_token_balances,
+        }))
+        .unwrap();
+        ResolvedTransaction {
+            slot: 1,
+            block_time: Some(1_700_000_000),
+            message: VersionedMessage::Legacy(Message::default()),
+            keys,
+            meta,
+        }
+    }
+
+    fn token_balance(index: u8, mint: &str, owner: &Pubkey, amount: &str) -> serde_json::Value {
+        json!({
+            "accountIndex": index,
+            "mint": mint,
+            "owner": owner.to_string(),
+            "uiTokenAmount": {
+                "amount": amount,
+                "decimals": 6,
+                "uiAmount": null,
+                "uiAmountString": "",
+            },
+        })
+    }
+
+    #[test]
+    fn closed_token_account_and_sol_changes() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique().to_string();
+        // Account 1 is emptied and closed, so it has no post balance. Its
+        // rent is refunded to the wallet.
+        let transaction = transaction(
+            &wallet,
+            json!([token_balance(1, &mint, &wallet, "2500000")]),
+            json!([]),
+        );
+
+        let entries = transaction_entries(&transaction, &wallet, "signature");
+        assert_eq!(entries.len(), 2);
+
+        let sol = &entries[0];
+        assert_eq!(sol.timestamp.as_deref(), Some("2023-11-14T22:13:20Z"));
+        assert_eq!(sol.mint, None);
+        assert_eq!(sol.symbol.as_deref(), Some("SOL"));
+        assert_eq!(sol.delta, "0.002034280");
+        assert_eq!(sol.fee.as_deref(), Some("0.000005000"));
+
+        let token = &entries[1];
+        assert_eq!(token.mint.as_deref(), Some(mint.as_str()));
+        assert_eq!(token.delta, "-2.500000");
+        assert_eq!(token.fee, None);
+    }
+
+    #[test]
+    fn token_changes_are_summed_per_mint_over_owned_accounts() {
+        let wallet = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mint = Pubkey::new_unique().to_string();
+        // The wallet moves tokens between its own accounts 1 and 2, and
+        // receives some from account 3 of another owner
+        let transaction = transaction(
+            &wallet,
+            json!([
+                token_balance(1, &mint, &wallet, "3000000"),
+                token_balance(2, &mint, &wallet, "0"),
+                token_balance(3, &mint, &other, "1000000"),
+            ]),
+            json!([
+                token_balance(1, &mint, &wallet, "1000000"),
+                token_balance(2, &mint, &wallet, "2500000"),
+                token_balance(3, &mint, &other, "500000"),
+            ]),
+        );
+
+        let entries = transaction_entries(&transaction, &wallet, "signature");
+        let tokens: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.mint.is_some())
+            .collect();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].delta, "0.500000");
+
+        // The other owner only sees its own account
+        let entries = transaction_entries(&transaction, &other, "signature");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].delta, "-0.500000");
+        assert_eq!(entries[0].fee, None);
+    }
+}
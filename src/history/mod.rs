@@ -0,0 +1,371 @@
+//! Listing the transactions that reference an address
+
+use futures::{stream, StreamExt};
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient as Client,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_request::MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{message::VersionedMessage, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransactionWithStatusMeta,
+    UiTransactionStatusMeta,
+};
+
+use crate::{
+    config::confirmed_commitment,
+    error::{ExplorerError, Result},
+    transaction::{
+        fetch_transaction, instructions::known_program_name,
+        loaded_addresses::resolve_loaded_addresses,
+    },
+    utils::{display_balance, print_json, OutputFormat},
+    History,
+};
+
+pub mod ledger;
+
+/// Number of transactions fetched concurrently for summaries and exports
+const CONCURRENT_FETCHES: usize = 8;
+
+/// Number of transactions listed when no `--limit` is given
+const DEFAULT_LIMIT: usize = 25;
+
+pub async fn handler(client: Client, output: OutputFormat, history: History) -> Result<()> {
+    if let Some(path) = &history.export {
+        return ledger::export(&client, output, &history, path).await;
+    }
+
+    let signatures = fetch_signatures(
+        &client,
+        &history.pubkey,
+        history.before,
+        history.until,
+        history.limit.unwrap_or(DEFAULT_LIMIT),
+    )
+    .await?;
+
+    // Optionally fetch every transaction to summarize it
+    let summaries: Vec<Option<TransactionSummary>> = if history.summary {
+        stream::iter(&signatures)
+            .map(|status| summarize(&client, &history.pubkey, status))
+            .buffered(CONCURRENT_FETCHES)
+            .collect()
+            .await
+    } else {
+        vec![None; signatures.len()]
+    };
+
+    let entries: Vec<HistoryEntry> = signatures
+        .into_iter()
+        .zip(summaries)
+        .map(|(status, summary)| HistoryEntry { status, summary })
+        .collect();
+
+    match output {
+        OutputFormat::Table => display_history(&history.pubkey, &entries, history.summary),
+        OutputFormat::Json => print_json(&HistoryView {
+            address: history.pubkey.to_string(),
+            transactions: entries.iter().map(HistoryEntry::to_view).collect(),
+        }),
+    }
+
+    Ok(())
+}
+
+/// Fetches up to `limit` signatures for transactions referencing `address`,
+/// newest first, paging backwards through the rpc's per-request limit
+pub async fn fetch_signatures(
+    client: &Client,
+    address: &Pubkey,
+    before: Option<Signature>,
+    until: Option<Signature>,
+    limit: usize,
+) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+    let mut signatures =
+        Vec::with_capacity(limit.min(MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT));
+    let mut before = before;
+    while signatures.len() < limit {
+        let page_limit =
+            (limit - signatures.len()).min(MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT);
+        let page = client
+            .get_signatures_for_address_with_config(
+                address,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(page_limit),
+                    commitment: Some(confirmed_commitment(client)),
+                },
+            )
+            .await?;
+
+        // A short page means there are no older transactions (or `until` was
+        // reached)
+        let done = page.len() < page_limit;
+        if let Some(last) = page.last() {
+            before = Some(last.signature.parse().map_err(|e| {
+                ExplorerError::Decode(format!("signature {}: {e}", last.signature))
+            })?);
+        }
+        signatures.extend(page);
+        if done {
+            break;
+        }
+    }
+
+    Ok(signatures)
+}
+
+pub struct HistoryEntry {
+    pub status: RpcConfirmedTransactionStatusWithSignature,
+    pub summary: Option<TransactionSummary>,
+}
+
+/// A one line summary of a transaction, from the address' point of view
+#[derive(Debug, Clone)]
+pub struct TransactionSummary {
+    pub fee_payer: Pubkey,
+    /// Programs invoked by top level instructions, in order of first use
+    pub programs: Vec<Pubkey>,
+    /// Change in the address' lamports, including any fee it paid
+    pub sol_delta: Option<i64>,
+}
+
+/// Fetches and summarizes a transaction. Failures (e.g. transactions pruned
+/// by the rpc node) are reported without aborting the listing.
+async fn summarize(
+    client: &Client,
+    address: &Pubkey,
+    status: &RpcConfirmedTransactionStatusWithSignature,
+) -> Option<TransactionSummary> {
+    let summary = async {
+        let signature: Signature = status
+            .signature
+            .parse()
+            .map_err(|e| ExplorerError::Decode(format!("signature {}: {e}", status.signature)))?;
+        let transaction = fetch_transaction(client, &signature).await?;
+        let transaction = ResolvedTransaction::new(transaction, client).await?;
+        Ok::<_, ExplorerError>(TransactionSummary::new(&transaction, address))
+    };
+
+    match summary.await {
+        Ok(summary) => Some(summary),
+        Err(e) => {
+            eprintln!("failed to summarize transaction {}: {e}", status.signature);
+            None
+        }
+    }
+}
+
+/// A fetched transaction with its account keys resolved
+pub struct ResolvedTransaction {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub message: VersionedMessage,
+    /// Static accounts, then loaded addresses, which is the order balances
+    /// are indexed by
+    pub keys: Vec<Pubkey>,
+    pub meta: UiTransactionStatusMeta,
+}
+
+impl ResolvedTransaction {
+    pub async fn new(
+        transaction: EncodedConfirmedTransactionWithStatusMeta,
+        client: &Client,
+    ) -> Result<ResolvedTransaction> {
+        let EncodedConfirmedTransactionWithStatusMeta {
+            slot,
+            transaction:
+                EncodedTransactionWithStatusMeta {
+                    transaction, meta, ..
+                },
+            block_time,
+        } = transaction;
+        let meta = meta.ok_or_else(|| {
+            ExplorerError::NotFound("transaction status metadata not found".to_string())
+        })?;
+        let message = transaction
+            .decode()
+            .ok_or_else(|| ExplorerError::Decode("transaction".to_string()))?
+            .message;
+
+        let mut keys = message.static_account_keys().to_vec();
+        if let VersionedMessage::V0(v0) = &message {
+            if !v0.address_table_lookups.is_empty() {
+                let loaded =
+                    resolve_loaded_addresses(&v0.address_table_lookups, &meta, client).await;
+                keys.extend(loaded.writable);
+                keys.extend(loaded.readonly);
+            }
+        }
+
+        Ok(ResolvedTransaction {
+            slot,
+            block_time,
+            message,
+            keys,
+            meta,
+        })
+    }
+
+    pub fn fee_payer(&self) -> Pubkey {
+        self.keys.first().copied().unwrap_or_default()
+    }
+
+    /// Change in `address`' lamports, including any fee it paid. `None` if the
+    /// address is not one of the transaction's accounts.
+    pub fn lamport_delta(&self, address: &Pubkey) -> Option<i64> {
+        let idx = self.keys.iter().position(|key| key == address)?;
+        let pre = *self.meta.pre_balances.get(idx)?;
+        let post = *self.meta.post_balances.get(idx)?;
+        Some(post as i64 - pre as i64)
+    }
+}
+
+impl TransactionSummary {
+    pub fn new(transaction: &ResolvedTransaction, address: &Pubkey) -> TransactionSummary {
+        let message = &transaction.message;
+
+        // Programs are always static accounts
+        let mut programs = vec![];
+        for instruction in message.instructions() {
+            let program_id = message
+                .static_account_keys()
+                .get(instruction.program_id_index as usize)
+                .copied()
+                .unwrap_or_default();
+            if !programs.contains(&program_id) {
+                programs.push(program_id);
+            }
+        }
+
+        TransactionSummary {
+            fee_payer: transaction.fee_payer(),
+            programs,
+            sol_delta: transaction.lamport_delta(address),
+        }
+    }
+
+    fn line(&self) -> String {
+        let programs = self
+            .programs
+            .iter()
+            .map(|program| known_program_name(program).map_or(program.to_string(), str::to_string))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sol_delta = self.sol_delta.map_or("?".to_string(), format_sol_delta);
+        format!("payer {} | {programs} | {sol_delta} SOL", self.fee_payer)
+    }
+
+    fn to_view(&self) -> TransactionSummaryView {
+        TransactionSummaryView {
+            fee_payer: self.fee_payer.to_string(),
+            programs: self.programs.iter().map(ToString::to_string).collect(),
+            sol_delta: self.sol_delta,
+        }
+    }
+}
+
+impl HistoryEntry {
+    fn to_view(&self) -> HistoryEntryView {
+        HistoryEntryView {
+            signature: self.status.signature.clone(),
+            slot: self.status.slot,
+            block_time: self.status.block_time,
+            success: self.status.err.is_none(),
+            error: self.status.err.as_ref().map(ToString::to_string),
+            memo: self.status.memo.clone(),
+            summary: self.summary.as_ref().map(TransactionSummary::to_view),
+        }
+    }
+}
+
+/// Serializable view model of an address' transaction history
+#[derive(Serialize)]
+pub struct HistoryView {
+    pub address: String,
+    /// Newest first
+    pub transactions: Vec<HistoryEntryView>,
+}
+
+#[derive(Serialize)]
+pub struct HistoryEntryView {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub memo: Option<String>,
+    /// Only fetched with `--summary`
+    pub summary: Option<TransactionSummaryView>,
+}
+
+#[derive(Serialize)]
+pub struct TransactionSummaryView {
+    pub fee_payer: String,
+    pub programs: Vec<String>,
+    /// In lamports
+    pub sol_delta: Option<i64>,
+}
+
+fn display_history(address: &Pubkey, entries: &[HistoryEntry], summary: bool) {
+    let mut history_table = Table::new();
+    history_table.set_titles(row![c->"Transaction History", address]);
+    history_table.add_row(row![c->"Transactions", entries.len()]);
+
+    let mut transactions_table = Table::new();
+    if summary {
+        transactions_table.set_titles(
+            row![c->"Signature", c->"Slot", c->"Time", c->"Result", c->"Memo", c->"Summary"],
+        );
+    } else {
+        transactions_table
+            .set_titles(row![c->"Signature", c->"Slot", c->"Time", c->"Result", c->"Memo"]);
+    }
+    for entry in entries {
+        let status = &entry.status;
+        let time = status
+            .block_time
+            .map_or("Unknown".to_string(), |time| time.to_string());
+        let memo = status.memo.as_deref().unwrap_or("");
+        let mut row = match &status.err {
+            None => row![status.signature, r->status.slot, time, Fg->"SUCCESS", memo],
+            Some(_) => row![status.signature, r->status.slot, time, Fr->"FAILED", memo],
+        };
+        if summary {
+            let line = entry
+                .summary
+                .as_ref()
+                .map_or("Unavailable".to_string(), TransactionSummary::line);
+            row.add_cell(prettytable::Cell::new(&line));
+        }
+        transactions_table.add_row(row);
+    }
+
+    use terminal_size::{terminal_size, Width};
+    let size = terminal_size();
+    let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+    let padded_width = width.saturating_sub(4);
+
+    let mut tables = Table::new();
+    tables.add_row(row![c->history_table]);
+    tables.add_row(row![" ".repeat(padded_width)]);
+    if !entries.is_empty() {
+        tables.add_row(row![c->transactions_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+    }
+    tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+    println!();
+    tables.printstd();
+    println!();
+}
+
+/// Formats a signed lamport amount in SOL, e.g. `-0.000005000`
+fn format_sol_delta(lamports: i64) -> String {
+    let sign = if lamports < 0 { "-" } else { "+" };
+    format!("{sign}{}", display_balance(lamports.unsigned_abs(), 9))
+}
@@ -1,11 +1,21 @@
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 
 use clap::Parser;
+use colored::Colorize;
+use config::{default_config_path, Config, ConfigCommand};
+use error::Result;
+use history::ledger::LedgerFormat;
+use program::ProgramCommand;
+use utils::OutputFormat;
 
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
 
 mod account;
 mod block;
+mod config;
+mod error;
+mod history;
+mod program;
 mod transaction;
 mod utils;
 
@@ -17,14 +27,20 @@ pub struct ExplorerCli {
     #[command(subcommand)]
     command: Command,
 
-    /// The url/endpoint to use for any rpc requests.
-    #[arg(
-        long,
-        short = 'u',
-        default_value = "http://api.mainnet-beta.solana.com",
-        global = true
-    )]
-    rpc_url: String,
+    /// The url/endpoint to use for any rpc requests. Also accepts cluster
+    /// aliases (e.g. devnet) and endpoint names from the config file.
+    /// [default: config's default_cluster, or mainnet]
+    #[arg(long, short = 'u', global = true)]
+    rpc_url: Option<String>,
+
+    /// The format in which results are printed. [default: config's output,
+    /// or table]
+    #[arg(long, short = 'o', value_enum, global = true)]
+    output: Option<OutputFormat>,
+
+    /// Path to the config file. [default: ~/.config/sol/config.toml]
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -37,6 +53,17 @@ pub enum Command {
 
     /// Provide a block's slot to inspect its contents
     Block(Block),
+
+    /// Provide an address to list the transactions that reference it
+    History(History),
+
+    /// Inspect programs, e.g. dump a program's executable
+    #[command(subcommand)]
+    Program(ProgramCommand),
+
+    /// View or edit the config file (named endpoints and defaults)
+    #[command(subcommand)]
+    Config(ConfigCommand),
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -44,6 +71,11 @@ pub struct Transaction {
     /// Signature (base58) of the transaction to inspect
     #[clap(value_parser = Signature::from_str)]
     signature: Signature,
+
+    /// JSON file(s) mapping program ids to custom error codes and names, used
+    /// to name custom program errors, e.g. {"<program id>": {"6000": "MyError"}}
+    #[clap(long = "error-table")]
+    error_tables: Vec<PathBuf>,
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -51,6 +83,18 @@ pub struct Account {
     /// Public key (base58) of the account to inspect
     #[clap(value_parser = Pubkey::from_str)]
     pubkey: Pubkey,
+
+    /// Write the raw account data to this file instead of displaying the
+    /// account. For upgradeable programs, program data and buffers, this is
+    /// the executable without the loader header.
+    #[clap(long)]
+    dump: Option<PathBuf>,
+
+    /// Fetch and display the off-chain JSON metadata (name, description,
+    /// image, attributes and files) at a token's Metaplex or Token-2022
+    /// metadata URI. Applies to mints and token accounts.
+    #[clap(long)]
+    fetch_uri: bool,
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -67,13 +111,76 @@ pub struct Block {
     verbose: bool,
 }
 
+#[derive(Debug, Parser, Clone)]
+pub struct History {
+    /// Public key (base58) of the address whose transactions to list
+    #[clap(value_parser = Pubkey::from_str)]
+    pubkey: Pubkey,
+
+    /// Maximum number of transactions to list, newest first. The rpc returns
+    /// at most 1000 per request, so larger limits are fetched page by page.
+    /// [default: 25, or every transaction with --export]
+    #[clap(long)]
+    limit: Option<usize>,
+
+    /// Only list transactions older than this signature
+    #[clap(long, value_parser = Signature::from_str)]
+    before: Option<Signature>,
+
+    /// Only list transactions newer than this signature
+    #[clap(long, value_parser = Signature::from_str)]
+    until: Option<Signature>,
+
+    /// Fetch every transaction to summarize its fee payer, the programs it
+    /// invoked and the address' SOL balance change
+    #[clap(long, short, default_value_t = false)]
+    summary: bool,
+
+    /// Instead of listing transactions, write a ledger of the address' SOL
+    /// and token balance changes to this file, one entry per asset per
+    /// transaction, oldest first. Includes the transactions of the token
+    /// accounts the address currently owns, but not of closed ones.
+    #[clap(long, conflicts_with = "summary")]
+    export: Option<PathBuf>,
+
+    /// Format of the exported ledger. [default: jsonl for .jsonl files,
+    /// otherwise csv]
+    #[clap(long, value_enum, requires = "export")]
+    export_format: Option<LedgerFormat>,
+}
+
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
     let args = ExplorerCli::parse();
 
-    match args.command {
-        Command::Transaction(transaction) => transaction::handler(args.rpc_url, transaction).await,
-        Command::Account(account) => account::handler(args.rpc_url, account).await,
-        Command::Block(block) => block::handler(args.rpc_url, block).await,
+    if let Err(e) = run(args).await {
+        eprintln!("{} {e}", "error:".red().bold());
+        std::process::exit(e.exit_code());
+    }
+}
+
+async fn run(args: ExplorerCli) -> Result<()> {
+    // Load config, with command line arguments taking precedence
+    let config_path = args.config.unwrap_or_else(default_config_path);
+    let config = Config::load(&config_path)?;
+    config.apply_color();
+    let output = args.output.or(config.output).unwrap_or(OutputFormat::Table);
+
+    // Config commands don't need an rpc client
+    let command = match args.command {
+        Command::Config(command) => return config::handler(&config_path, output, command),
+        command => command,
+    };
+    let client = config.client(args.rpc_url.as_deref())?;
+
+    match command {
+        Command::Transaction(transaction) => {
+            transaction::handler(client, output, transaction).await
+        }
+        Command::Account(account) => account::handler(client, output, account).await,
+        Command::Block(block) => block::handler(client, output, block).await,
+        Command::History(history) => history::handler(client, output, history).await,
+        Command::Program(command) => program::handler(client, output, command).await,
+        Command::Config(_) => unreachable!("handled above"),
     }
 }
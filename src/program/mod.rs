@@ -0,0 +1,276 @@
+//! Program commands
+
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use clap::Parser;
+use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient as Client,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    account::{
+        fetch_account, parse_accounts,
+        program::{dump_data, is_program_account},
+        AccountView, ParsedAccount,
+    },
+    error::{ExplorerError, Result},
+    utils::{display_balance, print_json, OutputFormat},
+};
+
+#[derive(Debug, Parser, Clone)]
+pub enum ProgramCommand {
+    /// Write a program's executable (ELF) to a file, e.g. to diff it against
+    /// a local build
+    Dump {
+        /// Program id (base58). Upgradeable programs are resolved to their
+        /// program data account.
+        #[clap(value_parser = Pubkey::from_str)]
+        program_id: Pubkey,
+
+        /// Path of the file to write, e.g. program.so
+        out: PathBuf,
+    },
+
+    /// List the accounts owned by a program, e.g. all token accounts of a
+    /// mint. Token, nonce, vote, lookup table and sysvar accounts are decoded.
+    Accounts {
+        /// Program id (base58) of the owner of the accounts to list
+        #[clap(value_parser = Pubkey::from_str)]
+        program_id: Pubkey,
+
+        /// Only list accounts whose data contains these bytes at this offset,
+        /// as offset:base58, e.g. 0:<mint> for token accounts of a mint. May
+        /// be repeated.
+        #[clap(long, value_parser = parse_memcmp)]
+        memcmp: Vec<Memcmp>,
+
+        /// Only list accounts with exactly this many bytes of data
+        #[clap(long)]
+        datasize: Option<u64>,
+
+        /// Only fetch part of each account's data, as offset:length. Sliced
+        /// accounts are not decoded, and their data length is that of the
+        /// slice, so 0:0 quickly lists keys and balances.
+        #[clap(long, value_parser = parse_data_slice)]
+        data_slice: Option<UiDataSliceConfig>,
+
+        /// Only print the number of matching accounts
+        #[clap(long, default_value_t = false)]
+        count_only: bool,
+    },
+}
+
+pub async fn handler(client: Client, output: OutputFormat, command: ProgramCommand) -> Result<()> {
+    match command {
+        ProgramCommand::Dump { program_id, out } => {
+            let account = fetch_account(&client, &program_id).await?;
+            if !is_program_account(&account) {
+                return Err(ExplorerError::Decode(format!(
+                    "program {program_id}: account is owned by {}, not a bpf loader",
+                    account.owner
+                )));
+            }
+
+            let data = dump_data(&program_id, account, &client).await?;
+            write_dump(&program_id, &data, &out, output)
+        }
+        ProgramCommand::Accounts {
+            program_id,
+            memcmp,
+            datasize,
+            data_slice,
+            count_only,
+        } => {
+            let mut filters: Vec<RpcFilterType> =
+                memcmp.into_iter().map(RpcFilterType::Memcmp).collect();
+            filters.extend(datasize.map(RpcFilterType::DataSize));
+
+            // Counting doesn't need any account data
+            let data_slice = if count_only {
+                Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: 0,
+                })
+            } else {
+                data_slice
+            };
+
+            let mut accounts = client
+                .get_program_accounts_with_config(
+                    &program_id,
+                    RpcProgramAccountsConfig {
+                        filters: (!filters.is_empty()).then_some(filters),
+                        account_config: RpcAccountInfoConfig {
+                            encoding: Some(UiAccountEncoding::Base64),
+                            data_slice,
+                            commitment: Some(client.commitment()),
+                            min_context_slot: None,
+                        },
+                        with_context: None,
+                    },
+                )
+                .await?;
+            accounts.sort_by_key(|(key, _account)| *key);
+
+            if count_only {
+                match output {
+                    OutputFormat::Table => {
+                        println!("{} accounts owned by {program_id}", accounts.len())
+                    }
+                    OutputFormat::Json => print_json(&ProgramAccountsView {
+                        program_id: program_id.to_string(),
+                        count: accounts.len(),
+                        accounts: None,
+                    }),
+                }
+                return Ok(());
+            }
+
+            // Parsers expect the whole account data
+            let parsed: Vec<ParsedAccount> = if data_slice.is_some() {
+                accounts
+                    .iter()
+                    .map(|(_key, account)| ParsedAccount::Other(account))
+                    .collect()
+            } else {
+                parse_accounts(&accounts, &client).await?
+            };
+
+            match output {
+                OutputFormat::Table => display_program_accounts(&program_id, &accounts, parsed),
+                OutputFormat::Json => print_json(&ProgramAccountsView {
+                    program_id: program_id.to_string(),
+                    count: accounts.len(),
+                    accounts: Some(
+                        accounts
+                            .iter()
+                            .zip(&parsed)
+                            .map(|((key, account), parsed)| parsed.to_view(account, key))
+                            .collect(),
+                    ),
+                }),
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Lists all accounts with their balance and data length, followed by the
+/// decoded contents of those that a parser applies to
+fn display_program_accounts(
+    program_id: &Pubkey,
+    accounts: &[(Pubkey, Account)],
+    parsed: Vec<ParsedAccount>,
+) {
+    let mut overview_table = Table::new();
+    overview_table.set_titles(row![c->"Program Accounts", program_id]);
+    overview_table.add_row(row![c->"Accounts", accounts.len()]);
+
+    let mut accounts_table = Table::new();
+    accounts_table.set_titles(row![c->"Account", c->"SOL Balance", c->"Data Length"]);
+    for (key, account) in accounts {
+        accounts_table.add_row(row![
+            key,
+            r->display_balance(account.lamports, 9),
+            r->account.data.len()
+        ]);
+    }
+
+    use terminal_size::{terminal_size, Width};
+    let size = terminal_size();
+    let width = size.map(|(Width(w), _height)| w as usize).unwrap_or(32);
+    let padded_width = width.saturating_sub(4);
+
+    let mut tables = Table::new();
+    tables.add_row(row![c->overview_table]);
+    tables.add_row(row![" ".repeat(padded_width)]);
+    if !accounts.is_empty() {
+        tables.add_row(row![c->accounts_table]);
+        tables.add_row(row![" ".repeat(padded_width)]);
+    }
+    tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
+    println!();
+    tables.printstd();
+
+    for ((key, _account), parsed) in accounts.iter().zip(parsed) {
+        if !matches!(parsed, ParsedAccount::Other(_)) {
+            parsed.display(key);
+        }
+    }
+    println!();
+}
+
+/// Serializable view model of a program's accounts
+#[derive(Serialize)]
+pub struct ProgramAccountsView {
+    pub program_id: String,
+    pub count: usize,
+    /// Omitted with `--count-only`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<Vec<AccountView>>,
+}
+
+/// Parses a memcmp filter given as offset:base58
+fn parse_memcmp(s: &str) -> std::result::Result<Memcmp, String> {
+    let (offset, bytes) = s
+        .split_once(':')
+        .ok_or_else(|| "expected offset:base58".to_string())?;
+    let offset = offset
+        .parse()
+        .map_err(|e| format!("invalid offset {offset}: {e}"))?;
+    let bytes = bs58::decode(bytes)
+        .into_vec()
+        .map_err(|e| format!("invalid base58 {bytes}: {e}"))?;
+    Ok(Memcmp::new_base58_encoded(offset, &bytes))
+}
+
+/// Parses a data slice given as offset:length
+fn parse_data_slice(s: &str) -> std::result::Result<UiDataSliceConfig, String> {
+    let (offset, length) = s
+        .split_once(':')
+        .ok_or_else(|| "expected offset:length".to_string())?;
+    Ok(UiDataSliceConfig {
+        offset: offset
+            .parse()
+            .map_err(|e| format!("invalid offset {offset}: {e}"))?,
+        length: length
+            .parse()
+            .map_err(|e| format!("invalid length {length}: {e}"))?,
+    })
+}
+
+/// Writes dumped account data to `path`, reporting what was written
+pub fn write_dump(key: &Pubkey, data: &[u8], path: &Path, output: OutputFormat) -> Result<()> {
+    std::fs::write(path, data)?;
+
+    match output {
+        OutputFormat::Table => {
+            println!("Wrote {} bytes of {key} to {}", data.len(), path.display())
+        }
+        OutputFormat::Json => print_json(&DumpView {
+            pubkey: key.to_string(),
+            path: path.display().to_string(),
+            bytes: data.len(),
+        }),
+    }
+
+    Ok(())
+}
+
+/// Serializable summary of a dump
+#[derive(Serialize)]
+pub struct DumpView {
+    pub pubkey: String,
+    pub path: String,
+    pub bytes: usize,
+}
@@ -0,0 +1,118 @@
+//! Nested cpi call tree with compute unit usage
+
+use num_format::{Locale, ToFormattedString};
+use prettytable::{row, Table};
+use serde::Serialize;
+
+use super::{
+    instructions::{known_program_name, ParsedInstruction},
+    logs::Invocation,
+};
+
+/// One node of the call tree. The tree is stored flattened in execution
+/// order, so the children of a node are the following entries one level
+/// deeper, up until the next entry at the same or a shallower depth.
+#[derive(Serialize)]
+pub struct CallTreeEntry {
+    pub program_id: String,
+    pub program: Option<&'static str>,
+    #[serde(rename = "type")]
+    pub instruction_type: Option<String>,
+    pub depth: u32,
+    /// Compute units consumed, including cpis
+    pub consumed: Option<u64>,
+    /// Compute units consumed by this invocation alone, excluding cpis
+    pub exclusive: Option<u64>,
+    pub success: Option<bool>,
+}
+
+/// Builds the call tree from the instructions' stack heights, taking compute
+/// units from the matching invocation in the logs.
+///
+/// Both the instructions and the logged invocations are in execution order,
+/// so they are matched up by walking them in lockstep. If the logs were
+/// truncated, the remaining entries have no compute unit data.
+pub fn build_call_tree(
+    instructions: &[ParsedInstruction],
+    invocations: &[Invocation],
+) -> Vec<CallTreeEntry> {
+    let mut entries: Vec<CallTreeEntry> = vec![];
+    let mut cursor = 0;
+
+    let flattened = instructions
+        .iter()
+        .flat_map(|ix| std::iter::once(ix).chain(ix.inner_instructions.iter()));
+    for instruction in flattened {
+        let program_id = instruction.program_id.to_string();
+        let invocation = invocations.get(cursor).filter(|invocation| {
+            invocation.program_id == program_id && invocation.depth == instruction.stack_height
+        });
+        if invocation.is_some() {
+            cursor += 1;
+        }
+
+        entries.push(CallTreeEntry {
+            program: known_program_name(&instruction.program_id),
+            program_id,
+            instruction_type: instruction
+                .decoded
+                .as_ref()
+                .map(|decoded| decoded.instruction_type.clone()),
+            depth: instruction.stack_height,
+            consumed: invocation.and_then(|invocation| invocation.consumed),
+            exclusive: None,
+            success: invocation.and_then(|invocation| invocation.success),
+        });
+    }
+
+    // Subtract direct children's compute units to get exclusive usage
+    for i in 0..entries.len() {
+        let depth = entries[i].depth;
+        let children_consumed: Option<u64> = entries[i + 1..]
+            .iter()
+            .take_while(|entry| entry.depth > depth)
+            .filter(|entry| entry.depth == depth + 1)
+            // Builtins don't log compute units, but did run if they completed
+            .map(|entry| entry.consumed.or(entry.success.map(|_| 0)))
+            .sum();
+        entries[i].exclusive = entries[i]
+            .consumed
+            .zip(children_consumed)
+            .map(|(consumed, children)| consumed.saturating_sub(children));
+    }
+
+    entries
+}
+
+pub fn call_tree_table(entries: &[CallTreeEntry]) -> Table {
+    let mut table = Table::new();
+    table.set_titles(row![
+        c->"Call Tree",
+        c->"Depth",
+        c->"Compute Units",
+        c->"Exclusive CUs"
+    ]);
+
+    let format_cus = |cus: Option<u64>| match cus {
+        Some(cus) => cus.to_formatted_string(&Locale::en),
+        None => "-".to_string(),
+    };
+
+    for entry in entries {
+        let indent = "   ".repeat(entry.depth.saturating_sub(1) as usize);
+        let branch = if entry.depth > 1 { "└─ " } else { "" };
+        let program = entry.program.unwrap_or(entry.program_id.as_str());
+        let label = match &entry.instruction_type {
+            Some(instruction_type) => format!("{indent}{branch}{program}: {instruction_type}"),
+            None => format!("{indent}{branch}{program}"),
+        };
+        table.add_row(row![
+            label,
+            r->entry.depth,
+            r->format_cus(entry.consumed),
+            r->format_cus(entry.exclusive)
+        ]);
+    }
+
+    table
+}
@@ -0,0 +1,567 @@
+//! Human readable transaction errors, including custom program error codes
+
+use std::{collections::HashMap, path::Path};
+
+use num_traits::FromPrimitive;
+use serde::Serialize;
+use solana_sdk::{
+    instruction::InstructionError, pubkey::Pubkey, system_instruction::SystemError, system_program,
+    transaction::TransactionError,
+};
+
+use crate::error::{ExplorerError, Result};
+
+use super::{instructions::ParsedInstruction, logs::Invocation};
+
+/// Custom error codes for specific programs, supplied by the user.
+///
+/// Tables are json files mapping program ids to error codes (decimal or
+/// `0x`-prefixed hex) to error names, e.g.
+///
+/// ```json
+/// { "<program id>": { "6000": "SlippageExceeded", "0x1771": "Stale" } }
+/// ```
+#[derive(Default)]
+pub struct ErrorTables(HashMap<Pubkey, HashMap<u32, String>>);
+
+impl ErrorTables {
+    pub fn load(paths: &[impl AsRef<Path>]) -> Result<ErrorTables> {
+        let mut tables = ErrorTables::default();
+        for path in paths {
+            let path = path.as_ref();
+            let invalid = |e: String| {
+                ExplorerError::Config(format!("invalid error table {}: {e}", path.display()))
+            };
+
+            let contents = std::fs::read_to_string(path).map_err(|e| {
+                ExplorerError::Config(format!(
+                    "failed to read error table {}: {e}",
+                    path.display()
+                ))
+            })?;
+            let programs: HashMap<String, HashMap<String, String>> =
+                serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+
+            for (program_id, errors) in programs {
+                let program_id: Pubkey = program_id
+                    .parse()
+                    .map_err(|e| invalid(format!("program id {program_id}: {e}")))?;
+                let entry = tables.0.entry(program_id).or_default();
+                for (code, name) in errors {
+                    let parsed = match code.strip_prefix("0x") {
+                        Some(hex) => u32::from_str_radix(hex, 16),
+                        None => code.parse(),
+                    };
+                    let code = parsed.map_err(|e| invalid(format!("error code {code}: {e}")))?;
+                    entry.insert(code, name);
+                }
+            }
+        }
+        Ok(tables)
+    }
+
+    fn get(&self, program_id: &Pubkey, code: u32) -> Option<&String> {
+        self.0.get(program_id)?.get(&code)
+    }
+}
+
+/// A decoded transaction error
+#[derive(Serialize)]
+pub struct TransactionErrorDetails {
+    pub error: String,
+    /// Index of the (top level) instruction that failed
+    pub instruction_index: Option<u8>,
+    /// The program that returned the error, which may be a cpi of the
+    /// failed top level instruction
+    pub program_id: Option<String>,
+    pub instruction_error: Option<String>,
+    pub custom_code: Option<u32>,
+    pub custom_error: Option<NamedError>,
+}
+
+#[derive(Serialize)]
+pub struct NamedError {
+    pub name: String,
+    pub message: Option<String>,
+}
+
+impl TransactionErrorDetails {
+    pub fn new(
+        error: &TransactionError,
+        instructions: &[ParsedInstruction],
+        invocations: &[Invocation],
+        tables: &ErrorTables,
+    ) -> TransactionErrorDetails {
+        let mut details = TransactionErrorDetails {
+            error: error.to_string(),
+            instruction_index: None,
+            program_id: None,
+            instruction_error: None,
+            custom_code: None,
+            custom_error: None,
+        };
+
+        let TransactionError::InstructionError(index, instruction_error) = error else {
+            return details;
+        };
+        details.instruction_index = Some(*index);
+        details.instruction_error = Some(instruction_error.to_string());
+
+        // Parents of a failed cpi fail too, and nothing is invoked after the
+        // innermost failure, so the last failed invocation is the program
+        // that returned the error. Fall back to the top level instruction's
+        // program if the logs were truncated.
+        let program_id: Option<Pubkey> = invocations
+            .iter()
+            .rev()
+            .find(|invocation| invocation.success == Some(false))
+            .and_then(|invocation| invocation.program_id.parse().ok())
+            .or_else(|| {
+                instructions
+                    .get(*index as usize)
+                    .map(|instruction| instruction.program_id)
+            });
+        details.program_id = program_id.map(|program_id| program_id.to_string());
+
+        if let (InstructionError::Custom(code), Some(program_id)) = (instruction_error, program_id)
+        {
+            details.custom_code = Some(*code);
+            details.custom_error = lookup_custom_error(&program_id, *code, tables);
+        }
+
+        details
+    }
+}
+
+fn lookup_custom_error(program_id: &Pubkey, code: u32, tables: &ErrorTables) -> Option<NamedError> {
+    // User supplied tables take precedence
+    if let Some(name) = tables.get(program_id, code) {
+        return Some(NamedError {
+            name: name.clone(),
+            message: None,
+        });
+    }
+
+    if *program_id == system_program::ID {
+        SystemError::from_u32(code).map(named)
+    } else if *program_id == spl_token::ID {
+        spl_token::error::TokenError::from_u32(code).map(named)
+    } else if *program_id == spl_token_2022::ID {
+        spl_token_2022::error::TokenError::from_u32(code).map(named)
+    } else if *program_id == ASSOCIATED_TOKEN_PROGRAM {
+        (code == 0).then(|| NamedError {
+            name: "InvalidOwner".to_string(),
+            message: Some(
+                "Associated token account owner does not match address derivation".to_string(),
+            ),
+        })
+    } else {
+        // Any other program may be an anchor program
+        ANCHOR_ERRORS
+            .iter()
+            .find(|(anchor_code, _, _)| *anchor_code == code)
+            .map(|(_, name, message)| NamedError {
+                name: name.to_string(),
+                message: Some(message.to_string()),
+            })
+    }
+}
+
+fn named<E: std::fmt::Debug + std::fmt::Display>(error: E) -> NamedError {
+    NamedError {
+        name: format!("{error:?}"),
+        message: Some(error.to_string()),
+    }
+}
+
+const ASSOCIATED_TOKEN_PROGRAM: Pubkey =
+    solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Anchor framework errors (codes 100 to 5000), from `anchor_lang::error::ErrorCode`.
+/// Programs' own anchor errors start at 6000 and must be supplied by the user.
+const ANCHOR_ERRORS: &[(u32, &str, &str)] = &[
+    (
+        100,
+        "InstructionMissing",
+        "8 byte instruction identifier not provided",
+    ),
+    (
+        101,
+        "InstructionFallbackNotFound",
+        "Fallback functions are not supported",
+    ),
+    (
+        102,
+        "InstructionDidNotDeserialize",
+        "The program could not deserialize the given instruction",
+    ),
+    (
+        103,
+        "InstructionDidNotSerialize",
+        "The program could not serialize the given instruction",
+    ),
+    (
+        1000,
+        "IdlInstructionStub",
+        "The program was compiled without idl instructions",
+    ),
+    (
+        1001,
+        "IdlInstructionInvalidProgram",
+        "Invalid program given to the IDL instruction",
+    ),
+    (
+        1002,
+        "IdlAccountNotEmpty",
+        "IDL account must be empty in order to resize, try closing first",
+    ),
+    (
+        1500,
+        "EventInstructionStub",
+        "The program was compiled without `event-cpi` feature",
+    ),
+    (2000, "ConstraintMut", "A mut constraint was violated"),
+    (
+        2001,
+        "ConstraintHasOne",
+        "A has one constraint was violated",
+    ),
+    (2002, "ConstraintSigner", "A signer constraint was violated"),
+    (2003, "ConstraintRaw", "A raw constraint was violated"),
+    (2004, "ConstraintOwner", "An owner constraint was violated"),
+    (
+        2005,
+        "ConstraintRentExempt",
+        "A rent exemption constraint was violated",
+    ),
+    (2006, "ConstraintSeeds", "A seeds constraint was violated"),
+    (
+        2007,
+        "ConstraintExecutable",
+        "An executable constraint was violated",
+    ),
+    (
+        2008,
+        "ConstraintState",
+        "Deprecated Error, feel free to replace with something else",
+    ),
+    (
+        2009,
+        "ConstraintAssociated",
+        "An associated constraint was violated",
+    ),
+    (
+        2010,
+        "ConstraintAssociatedInit",
+        "An associated init constraint was violated",
+    ),
+    (2011, "ConstraintClose", "A close constraint was violated"),
+    (
+        2012,
+        "ConstraintAddress",
+        "An address constraint was violated",
+    ),
+    (2013, "ConstraintZero", "Expected zero account discriminant"),
+    (
+        2014,
+        "ConstraintTokenMint",
+        "A token mint constraint was violated",
+    ),
+    (
+        2015,
+        "ConstraintTokenOwner",
+        "A token owner constraint was violated",
+    ),
+    (
+        2016,
+        "ConstraintMintMintAuthority",
+        "A mint mint authority constraint was violated",
+    ),
+    (
+        2017,
+        "ConstraintMintFreezeAuthority",
+        "A mint freeze authority constraint was violated",
+    ),
+    (
+        2018,
+        "ConstraintMintDecimals",
+        "A mint decimals constraint was violated",
+    ),
+    (2019, "ConstraintSpace", "A space constraint was violated"),
+    (
+        2020,
+        "ConstraintAccountIsNone",
+        "A required account for the constraint is None",
+    ),
+    (
+        2021,
+        "ConstraintTokenTokenProgram",
+        "A token account token program constraint was violated",
+    ),
+    (
+        2022,
+        "ConstraintMintTokenProgram",
+        "A mint token program constraint was violated",
+    ),
+    (
+        2023,
+        "ConstraintAssociatedTokenTokenProgram",
+        "An associated token account token program constraint was violated",
+    ),
+    (
+        2024,
+        "ConstraintMintGroupPointerExtension",
+        "A group pointer extension constraint was violated",
+    ),
+    (
+        2025,
+        "ConstraintMintGroupPointerExtensionAuthority",
+        "A group pointer extension authority constraint was violated",
+    ),
+    (
+        2026,
+        "ConstraintMintGroupPointerExtensionGroupAddress",
+        "A group pointer extension group address constraint was violated",
+    ),
+    (
+        2027,
+        "ConstraintMintGroupMemberPointerExtension",
+        "A group member pointer extension constraint was violated",
+    ),
+    (
+        2028,
+        "ConstraintMintGroupMemberPointerExtensionAuthority",
+        "A group member pointer extension authority constraint was violated",
+    ),
+    (
+        2029,
+        "ConstraintMintGroupMemberPointerExtensionMemberAddress",
+        "A group member pointer extension group address constraint was violated",
+    ),
+    (
+        2030,
+        "ConstraintMintMetadataPointerExtension",
+        "A metadata pointer extension constraint was violated",
+    ),
+    (
+        2031,
+        "ConstraintMintMetadataPointerExtensionAuthority",
+        "A metadata pointer extension authority constraint was violated",
+    ),
+    (
+        2032,
+        "ConstraintMintMetadataPointerExtensionMetadataAddress",
+        "A metadata pointer extension metadata address constraint was violated",
+    ),
+    (
+        2033,
+        "ConstraintMintCloseAuthorityExtension",
+        "A close authority constraint was violated",
+    ),
+    (
+        2034,
+        "ConstraintMintCloseAuthorityExtensionAuthority",
+        "A close authority extension authority constraint was violated",
+    ),
+    (
+        2035,
+        "ConstraintMintPermanentDelegateExtension",
+        "A permanent delegate extension constraint was violated",
+    ),
+    (
+        2036,
+        "ConstraintMintPermanentDelegateExtensionDelegate",
+        "A permanent delegate extension delegate constraint was violated",
+    ),
+    (
+        2037,
+        "ConstraintMintTransferHookExtension",
+        "A transfer hook extension constraint was violated",
+    ),
+    (
+        2038,
+        "ConstraintMintTransferHookExtensionAuthority",
+        "A transfer hook extension authority constraint was violated",
+    ),
+    (
+        2039,
+        "ConstraintMintTransferHookExtensionProgramId",
+        "A transfer hook extension transfer hook program id constraint was violated",
+    ),
+    (2500, "RequireViolated", "A require expression was violated"),
+    (
+        2501,
+        "RequireEqViolated",
+        "A require_eq expression was violated",
+    ),
+    (
+        2502,
+        "RequireKeysEqViolated",
+        "A require_keys_eq expression was violated",
+    ),
+    (
+        2503,
+        "RequireNeqViolated",
+        "A require_neq expression was violated",
+    ),
+    (
+        2504,
+        "RequireKeysNeqViolated",
+        "A require_keys_neq expression was violated",
+    ),
+    (
+        2505,
+        "RequireGtViolated",
+        "A require_gt expression was violated",
+    ),
+    (
+        2506,
+        "RequireGteViolated",
+        "A require_gte expression was violated",
+    ),
+    (
+        3000,
+        "AccountDiscriminatorAlreadySet",
+        "The account discriminator was already set on this account",
+    ),
+    (
+        3001,
+        "AccountDiscriminatorNotFound",
+        "No 8 byte discriminator was found on the account",
+    ),
+    (
+        3002,
+        "AccountDiscriminatorMismatch",
+        "8 byte discriminator did not match what was expected",
+    ),
+    (
+        3003,
+        "AccountDidNotDeserialize",
+        "Failed to deserialize the account",
+    ),
+    (
+        3004,
+        "AccountDidNotSerialize",
+        "Failed to serialize the account",
+    ),
+    (
+        3005,
+        "AccountNotEnoughKeys",
+        "Not enough account keys given to the instruction",
+    ),
+    (
+        3006,
+        "AccountNotMutable",
+        "The given account is not mutable",
+    ),
+    (
+        3007,
+        "AccountOwnedByWrongProgram",
+        "The given account is owned by a different program than expected",
+    ),
+    (3008, "InvalidProgramId", "Program ID was not as expected"),
+    (
+        3009,
+        "InvalidProgramExecutable",
+        "Program account is not executable",
+    ),
+    (3010, "AccountNotSigner", "The given account did not sign"),
+    (
+        3011,
+        "AccountNotSystemOwned",
+        "The given account is not owned by the system program",
+    ),
+    (
+        3012,
+        "AccountNotInitialized",
+        "The program expected this account to be already initialized",
+    ),
+    (
+        3013,
+        "AccountNotProgramData",
+        "The given account is not a program data account",
+    ),
+    (
+        3014,
+        "AccountNotAssociatedTokenAccount",
+        "The given account is not the associated token account",
+    ),
+    (
+        3015,
+        "AccountSysvarMismatch",
+        "The given public key does not match the required sysvar",
+    ),
+    (
+        3016,
+        "AccountReallocExceedsLimit",
+        "The account reallocation exceeds the MAX_PERMITTED_DATA_INCREASE limit",
+    ),
+    (
+        3017,
+        "AccountDuplicateReallocs",
+        "The account was duplicated for more than one reallocation",
+    ),
+    (
+        4100,
+        "DeclaredProgramIdMismatch",
+        "The declared program id does not match the actual program id",
+    ),
+    (
+        4101,
+        "TryingToInitPayerAsProgramAccount",
+        "You cannot/should not initialize the payer account as a program account",
+    ),
+    (
+        4102,
+        "InvalidNumericConversion",
+        "Error during numeric conversion",
+    ),
+    (
+        5000,
+        "Deprecated",
+        "The API being used is deprecated and should no longer be used",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::logs::parse_logs;
+
+    #[test]
+    fn custom_error_of_nested_cpi_is_named_by_the_innermost_program() {
+        let dex = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+        let token = spl_token::ID.to_string();
+        let logs: Vec<String> = [
+            format!("Program {dex} invoke [1]"),
+            format!("Program {dex} consumed 1200 of 200000 compute units"),
+            format!("Program {dex} success"),
+            format!("Program {dex} invoke [1]"),
+            "Program log: Instruction: Swap".to_string(),
+            format!("Program {token} invoke [2]"),
+            "Program log: Instruction: Transfer".to_string(),
+            "Program log: Error: insufficient funds".to_string(),
+            format!("Program {token} consumed 4645 of 180000 compute units"),
+            format!("Program {token} failed: custom program error: 0x1"),
+            format!("Program {dex} consumed 24000 of 198800 compute units"),
+            format!("Program {dex} failed: custom program error: 0x1"),
+        ]
+        .into();
+        let (_lines, invocations) = parse_logs(&logs);
+
+        let details = TransactionErrorDetails::new(
+            &TransactionError::InstructionError(1, InstructionError::Custom(1)),
+            &[],
+            &invocations,
+            &ErrorTables::default(),
+        );
+
+        assert_eq!(details.instruction_index, Some(1));
+        assert_eq!(details.program_id.as_deref(), Some(token.as_str()));
+        assert_eq!(details.custom_code, Some(1));
+        assert_eq!(
+            details.custom_error.map(|error| error.name).as_deref(),
+            Some("InsufficientFunds")
+        );
+    }
+}
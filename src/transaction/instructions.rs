@@ -0,0 +1,392 @@
+//! Decoding top level and inner instructions
+
+use prettytable::{row, Table};
+use serde::Serialize;
+use serde_json::Value;
+use solana_sdk::{
+    instruction::{AccountMeta, CompiledInstruction},
+    message::AccountKeys,
+    pubkey,
+    pubkey::Pubkey,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, parse_instruction, UiInnerInstructions, UiInstruction,
+};
+
+use crate::utils::{insert_newlines, to_hex};
+
+/// Programs that are recognized by name throughout the explorer
+const KNOWN_PROGRAMS: &[(Pubkey, &str)] = &[
+    (
+        pubkey!("11111111111111111111111111111111"),
+        "System Program",
+    ),
+    (
+        pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        "Token Program",
+    ),
+    (
+        pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"),
+        "Token-2022 Program",
+    ),
+    (
+        pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
+        "Associated Token Account Program",
+    ),
+    (
+        pubkey!("ComputeBudget111111111111111111111111111111"),
+        "Compute Budget Program",
+    ),
+    (
+        pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"),
+        "Memo Program (v1)",
+    ),
+    (
+        pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+        "Memo Program",
+    ),
+    (
+        pubkey!("AddressLookupTab1e1111111111111111111111111"),
+        "Address Lookup Table Program",
+    ),
+    (
+        pubkey!("Stake11111111111111111111111111111111111111"),
+        "Stake Program",
+    ),
+    (
+        pubkey!("Vote111111111111111111111111111111111111111"),
+        "Vote Program",
+    ),
+    (
+        pubkey!("Config1111111111111111111111111111111111111"),
+        "Config Program",
+    ),
+    (
+        pubkey!("BPFLoader2111111111111111111111111111111111"),
+        "BPF Loader",
+    ),
+    (
+        pubkey!("BPFLoaderUpgradeab1e11111111111111111111111"),
+        "BPF Upgradeable Loader",
+    ),
+    (
+        pubkey!("Ed25519SigVerify111111111111111111111111111"),
+        "Ed25519 SigVerify Program",
+    ),
+    (
+        pubkey!("KeccakSecp256k11111111111111111111111111111"),
+        "Secp256k1 SigVerify Program",
+    ),
+    (
+        pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
+        "Metaplex Token Metadata Program",
+    ),
+];
+
+const COMPUTE_BUDGET_PROGRAM: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// Returns a human readable name for well known programs
+pub fn known_program_name(program_id: &Pubkey) -> Option<&'static str> {
+    KNOWN_PROGRAMS
+        .iter()
+        .find(|(id, _)| id == program_id)
+        .map(|(_, name)| *name)
+}
+
+pub struct ParsedInstruction {
+    pub program_id: Pubkey,
+    /// Accounts passed to the instruction, with their roles in the transaction
+    pub accounts: Vec<AccountMeta>,
+    pub decoded: Option<DecodedInstruction>,
+    pub data: Vec<u8>,
+    /// 1 for top level instructions, > 1 for cross-program invocations
+    pub stack_height: u32,
+    /// Inner instructions invoked by this instruction, in execution order
+    /// (only populated for top level instructions)
+    pub inner_instructions: Vec<ParsedInstruction>,
+}
+
+pub struct DecodedInstruction {
+    pub instruction_type: String,
+    pub args: Value,
+}
+
+impl ParsedInstruction {
+    fn new(
+        instruction: &CompiledInstruction,
+        transaction_accounts: &[AccountMeta],
+        stack_height: u32,
+    ) -> ParsedInstruction {
+        let keys: Vec<Pubkey> = transaction_accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        // Indices may be out of range if an address lookup failed, in which
+        // case the default pubkey is shown
+        let program_id = keys
+            .get(instruction.program_id_index as usize)
+            .copied()
+            .unwrap_or_default();
+        let accounts = instruction
+            .accounts
+            .iter()
+            .map(|&idx| {
+                transaction_accounts
+                    .get(idx as usize)
+                    .cloned()
+                    .unwrap_or(AccountMeta::new_readonly(Pubkey::default(), false))
+            })
+            .collect();
+
+        ParsedInstruction {
+            program_id,
+            accounts,
+            decoded: decode_instruction(&program_id, instruction, &keys, stack_height),
+            data: instruction.data.clone(),
+            stack_height,
+            inner_instructions: vec![],
+        }
+    }
+
+    pub fn program_label(&self) -> String {
+        match known_program_name(&self.program_id) {
+            Some(name) => format!("{name} ({})", self.program_id),
+            None => self.program_id.to_string(),
+        }
+    }
+
+    pub fn to_view(&self) -> InstructionView {
+        InstructionView {
+            program_id: self.program_id.to_string(),
+            program: known_program_name(&self.program_id),
+            stack_height: self.stack_height,
+            instruction_type: self
+                .decoded
+                .as_ref()
+                .map(|decoded| decoded.instruction_type.clone()),
+            args: self.decoded.as_ref().map(|decoded| decoded.args.clone()),
+            accounts: self
+                .accounts
+                .iter()
+                .map(|account| InstructionAccountView {
+                    pubkey: account.pubkey.to_string(),
+                    signer: account.is_signer,
+                    writable: account.is_writable,
+                })
+                .collect(),
+            data: to_hex(&self.data),
+            inner_instructions: self
+                .inner_instructions
+                .iter()
+                .map(ParsedInstruction::to_view)
+                .collect(),
+        }
+    }
+
+    /// Builds a two column table describing this instruction. `width` is used
+    /// to wrap long raw data.
+    pub fn table(&self, label: &str, width: usize) -> Table {
+        let mut table = Table::new();
+        table.set_titles(row![c->label, self.program_label()]);
+
+        match &self.decoded {
+            Some(DecodedInstruction {
+                instruction_type,
+                args,
+            }) => {
+                table.add_row(row![c->"Type", instruction_type]);
+                match args {
+                    Value::Object(map) => {
+                        for (key, value) in map {
+                            table.add_row(row![c->key, format_arg(value)]);
+                        }
+                    }
+                    Value::Null => {}
+                    other => {
+                        table.add_row(row![c->"Data", format_arg(other)]);
+                    }
+                }
+            }
+            None => {
+                table.add_row(row![c->"Data (hex)", insert_newlines(&to_hex(&self.data), width)]);
+            }
+        }
+
+        for (idx, account) in self.accounts.iter().enumerate() {
+            let mut roles = vec![];
+            if account.is_signer {
+                roles.push("signer");
+            }
+            if account.is_writable {
+                roles.push("writable");
+            }
+            let roles = if roles.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", roles.join(", "))
+            };
+            table.add_row(row![c->format!("Account {idx}"), format!("{}{roles}", account.pubkey)]);
+        }
+
+        table
+    }
+}
+
+/// Serializable view model of a [`ParsedInstruction`]
+#[derive(Serialize)]
+pub struct InstructionView {
+    pub program_id: String,
+    pub program: Option<&'static str>,
+    pub stack_height: u32,
+    #[serde(rename = "type")]
+    pub instruction_type: Option<String>,
+    pub args: Option<Value>,
+    pub accounts: Vec<InstructionAccountView>,
+    /// Raw instruction data (hex)
+    pub data: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inner_instructions: Vec<InstructionView>,
+}
+
+#[derive(Serialize)]
+pub struct InstructionAccountView {
+    pub pubkey: String,
+    pub signer: bool,
+    pub writable: bool,
+}
+
+/// Parses all top level instructions and attaches their inner instructions.
+///
+/// `transaction_accounts` must be ordered as the message's account keys
+/// (static keys followed by writable and readonly lookups).
+pub fn parse_instructions(
+    instructions: &[CompiledInstruction],
+    inner_instructions: &OptionSerializer<Vec<UiInnerInstructions>>,
+    transaction_accounts: &[AccountMeta],
+) -> Vec<ParsedInstruction> {
+    let mut parsed: Vec<ParsedInstruction> = instructions
+        .iter()
+        .map(|ix| ParsedInstruction::new(ix, transaction_accounts, 1))
+        .collect();
+
+    let inner_instructions: Option<&Vec<UiInnerInstructions>> = inner_instructions.as_ref().into();
+    for inner in inner_instructions.into_iter().flatten() {
+        let Some(parent) = parsed.get_mut(inner.index as usize) else {
+            continue;
+        };
+        for ui_instruction in &inner.instructions {
+            // We request a binary encoding, so the rpc returns compiled instructions
+            let UiInstruction::Compiled(compiled) = ui_instruction else {
+                continue;
+            };
+            let Ok(data) = bs58::decode(&compiled.data).into_vec() else {
+                continue;
+            };
+            let instruction = CompiledInstruction {
+                program_id_index: compiled.program_id_index,
+                accounts: compiled.accounts.clone(),
+                data,
+            };
+            // Older transactions do not record stack height, in which case
+            // the best we can say is that these are cpis from the top level.
+            let stack_height = compiled.stack_height.unwrap_or(2);
+            parent.inner_instructions.push(ParsedInstruction::new(
+                &instruction,
+                transaction_accounts,
+                stack_height,
+            ));
+        }
+    }
+
+    parsed
+}
+
+fn decode_instruction(
+    program_id: &Pubkey,
+    instruction: &CompiledInstruction,
+    keys: &[Pubkey],
+    stack_height: u32,
+) -> Option<DecodedInstruction> {
+    if *program_id == COMPUTE_BUDGET_PROGRAM {
+        return decode_compute_budget(&instruction.data);
+    }
+
+    let parsed = parse_instruction::parse(
+        program_id,
+        instruction,
+        &AccountKeys::new(keys, None),
+        Some(stack_height),
+    )
+    .ok()?;
+
+    // Most programs are parsed into {"type": .., "info": ..}, while memos are
+    // parsed into a bare string.
+    match parsed.parsed {
+        Value::Object(mut map) => {
+            let instruction_type = match map.remove("type") {
+                Some(Value::String(instruction_type)) => instruction_type,
+                _ => return None,
+            };
+            Some(DecodedInstruction {
+                instruction_type,
+                args: map.remove("info").unwrap_or(Value::Null),
+            })
+        }
+        memo @ Value::String(_) => Some(DecodedInstruction {
+            instruction_type: "memo".to_string(),
+            args: memo,
+        }),
+        _ => None,
+    }
+}
+
+/// The compute budget program is not supported by the rpc parsers, so the
+/// (borsh) instruction layout is decoded by hand.
+fn decode_compute_budget(data: &[u8]) -> Option<DecodedInstruction> {
+    let (&tag, rest) = data.split_first()?;
+    let read_u32 = |bytes: &[u8]| -> Option<u32> {
+        Some(u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?))
+    };
+    let read_u64 = |bytes: &[u8]| -> Option<u64> {
+        Some(u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?))
+    };
+
+    let (instruction_type, args) = match tag {
+        0 => (
+            "requestUnitsDeprecated",
+            serde_json::json!({
+                "units": read_u32(rest)?,
+                "additionalFee": read_u32(rest.get(4..)?)?,
+            }),
+        ),
+        1 => (
+            "requestHeapFrame",
+            serde_json::json!({ "bytes": read_u32(rest)? }),
+        ),
+        2 => (
+            "setComputeUnitLimit",
+            serde_json::json!({ "units": read_u32(rest)? }),
+        ),
+        3 => (
+            "setComputeUnitPrice",
+            serde_json::json!({ "microLamports": read_u64(rest)? }),
+        ),
+        4 => (
+            "setLoadedAccountsDataSizeLimit",
+            serde_json::json!({ "bytes": read_u32(rest)? }),
+        ),
+        _ => return None,
+    };
+
+    Some(DecodedInstruction {
+        instruction_type: instruction_type.to_string(),
+        args,
+    })
+}
+
+fn format_arg(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
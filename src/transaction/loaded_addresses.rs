@@ -0,0 +1,139 @@
+//! Resolving the accounts a v0 transaction loaded from address lookup tables
+
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient as Client;
+use solana_sdk::{
+    address_lookup_table::state::AddressLookupTable, message::v0::MessageAddressTableLookup,
+    pubkey::Pubkey,
+};
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionStatusMeta};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadedAddressesSource {
+    /// The addresses recorded in the transaction's status metadata, i.e. as
+    /// they were when the transaction was executed
+    Metadata,
+    /// The current state of the lookup tables, which may have been extended,
+    /// deactivated or closed since the transaction was executed
+    LiveLookup,
+}
+
+/// Addresses loaded from lookup tables, in the order the runtime appends them
+/// to the static accounts: all writable addresses, then all readonly ones
+pub struct LoadedAddresses {
+    pub writable: Vec<Pubkey>,
+    pub readonly: Vec<Pubkey>,
+    pub source: LoadedAddressesSource,
+    /// Inconsistencies found while resolving the addresses, such as a count
+    /// mismatch between the metadata and the message's lookups. Unresolvable
+    /// addresses are replaced by the default pubkey to keep indices aligned.
+    pub warnings: Vec<String>,
+}
+
+/// Resolves loaded addresses from the transaction metadata, only falling back
+/// to looking up the tables' current state if the rpc omitted them. Metadata
+/// addresses are trusted as is: only their counts are checked against the
+/// message's lookups, since comparing them with the tables' entries would
+/// mean fetching every table.
+pub async fn resolve_loaded_addresses(
+    lookups: &[MessageAddressTableLookup],
+    meta: &UiTransactionStatusMeta,
+    client: &Client,
+) -> LoadedAddresses {
+    match &meta.loaded_addresses {
+        OptionSerializer::Some(loaded) => {
+            let mut warnings = vec![];
+            let mut parse = |addresses: &[String]| -> Vec<Pubkey> {
+                addresses
+                    .iter()
+                    .map(|address| {
+                        address.parse().unwrap_or_else(|_| {
+                            warnings.push(format!("invalid loaded address {address} in metadata"));
+                            Pubkey::default()
+                        })
+                    })
+                    .collect()
+            };
+            let writable = parse(&loaded.writable);
+            let readonly = parse(&loaded.readonly);
+
+            // The metadata should have exactly one address per lookup index, so
+            // a count mismatch means indices past it no longer line up
+            let expected_writable: usize = lookups.iter().map(|l| l.writable_indexes.len()).sum();
+            let expected_readonly: usize = lookups.iter().map(|l| l.readonly_indexes.len()).sum();
+            if (writable.len(), readonly.len()) != (expected_writable, expected_readonly) {
+                warnings.push(format!(
+                    "loaded address count mismatch: metadata lists {} writable and {} readonly loaded addresses, but the message looks up {expected_writable} and {expected_readonly}, so accounts after the static ones may be misattributed",
+                    writable.len(),
+                    readonly.len(),
+                ));
+            }
+
+            LoadedAddresses {
+                writable,
+                readonly,
+                source: LoadedAddressesSource::Metadata,
+                warnings,
+            }
+        }
+        OptionSerializer::None | OptionSerializer::Skip => live_lookup(lookups, client).await,
+    }
+}
+
+async fn live_lookup(lookups: &[MessageAddressTableLookup], client: &Client) -> LoadedAddresses {
+    let mut warnings = vec![
+        "loaded addresses were not returned by the rpc, so they were resolved from the lookup tables' current state, which may differ from when the transaction was executed".to_string(),
+    ];
+
+    let tables = futures::future::join_all(
+        lookups
+            .iter()
+            .map(|lookup| client.get_account_data(&lookup.account_key)),
+    )
+    .await;
+
+    let mut writable = vec![];
+    let mut readonly = vec![];
+    for (lookup, table) in lookups.iter().zip(tables) {
+        let addresses: Option<Vec<Pubkey>> = match table {
+            Ok(data) => match AddressLookupTable::deserialize(&data) {
+                Ok(table) => Some(table.addresses.into_owned()),
+                Err(e) => {
+                    warnings.push(format!(
+                        "failed to decode lookup table {}: {e}",
+                        lookup.account_key
+                    ));
+                    None
+                }
+            },
+            Err(e) => {
+                warnings.push(format!(
+                    "failed to fetch lookup table {} (it may have been closed): {e}",
+                    lookup.account_key
+                ));
+                None
+            }
+        };
+
+        let mut resolve = |idx: u8| match &addresses {
+            Some(addresses) => addresses.get(idx as usize).copied().unwrap_or_else(|| {
+                warnings.push(format!(
+                    "lookup table {} has no address at index {idx}",
+                    lookup.account_key
+                ));
+                Pubkey::default()
+            }),
+            None => Pubkey::default(),
+        };
+        writable.extend(lookup.writable_indexes.iter().map(|&idx| resolve(idx)));
+        readonly.extend(lookup.readonly_indexes.iter().map(|&idx| resolve(idx)));
+    }
+
+    LoadedAddresses {
+        writable,
+        readonly,
+        source: LoadedAddressesSource::LiveLookup,
+        warnings,
+    }
+}
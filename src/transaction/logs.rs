@@ -0,0 +1,240 @@
+//! Parsing transaction logs into structured, per-invocation events
+
+use base64::Engine;
+use colored::{Color, Colorize};
+use prettytable::{row, Table};
+use serde::Serialize;
+
+use crate::utils::{insert_newlines, to_hex};
+
+use super::instructions::known_program_name;
+
+/// A single program invocation, as reported by the runtime's logs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub program_id: String,
+    /// Invoke depth, i.e. 1 for top level instructions
+    pub depth: u32,
+    /// Compute units consumed by this invocation (including its cpis).
+    /// Builtin programs do not report this.
+    pub consumed: Option<u64>,
+    /// Compute units that were available at the start of this invocation
+    pub budget: Option<u64>,
+    /// Whether the invocation succeeded (`None` if logs were truncated)
+    pub success: Option<bool>,
+}
+
+/// A log line attributed to the invocation that emitted it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogLine {
+    /// Index into the invocations returned alongside this line, if the line
+    /// was emitted within an invocation
+    #[serde(skip)]
+    pub invocation: Option<usize>,
+    pub program_id: Option<String>,
+    /// Invoke depth of the emitting program (0 outside of any invocation)
+    pub depth: u32,
+    #[serde(flatten)]
+    pub event: LogEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LogEvent {
+    /// `Program <id> invoke [<depth>]`
+    Invoke,
+    /// `Program log: <message>`
+    Log { message: String },
+    /// `Program data: <base64> <base64> ..`, decoded to hex
+    Data { data: Vec<String> },
+    /// `Program return: <id> <base64>`, decoded to hex
+    Return { data: String },
+    /// `Program <id> consumed <consumed> of <budget> compute units`
+    Consumed { consumed: u64, budget: u64 },
+    /// `Program <id> success`
+    Success,
+    /// `Program <id> failed: <error>`
+    Failed { error: String },
+    /// Anything else, e.g. `Log truncated` or programs using `sol_log` directly
+    Other { message: String },
+}
+
+/// Walks the logs, tracking the invoke stack, and attributes every line to
+/// the invocation that emitted it. Also returns all invocations in execution
+/// order.
+pub fn parse_logs(logs: &[String]) -> (Vec<LogLine>, Vec<Invocation>) {
+    let mut lines: Vec<LogLine> = Vec::with_capacity(logs.len());
+    let mut invocations: Vec<Invocation> = vec![];
+    let mut stack: Vec<usize> = vec![];
+
+    for log in logs {
+        let current = stack.last().copied();
+        let mut line = LogLine {
+            invocation: current,
+            program_id: current.map(|idx| invocations[idx].program_id.clone()),
+            depth: current.map(|idx| invocations[idx].depth).unwrap_or(0),
+            event: LogEvent::Other {
+                message: log.clone(),
+            },
+        };
+
+        if let Some(message) = log.strip_prefix("Program log: ") {
+            line.event = LogEvent::Log {
+                message: message.to_string(),
+            };
+        } else if let Some(data) = log.strip_prefix("Program data: ") {
+            line.event = LogEvent::Data {
+                data: data.split_whitespace().map(base64_to_hex).collect(),
+            };
+        } else if let Some(rest) = log.strip_prefix("Program return: ") {
+            if let Some((_program_id, data)) = rest.split_once(' ') {
+                line.event = LogEvent::Return {
+                    data: base64_to_hex(data),
+                };
+            }
+        } else if let Some((program_id, event)) = log
+            .strip_prefix("Program ")
+            .and_then(|rest| rest.split_once(' '))
+        {
+            if let Some(depth) = event
+                .strip_prefix("invoke [")
+                .and_then(|d| d.strip_suffix(']'))
+                .and_then(|d| d.parse().ok())
+            {
+                stack.push(invocations.len());
+                invocations.push(Invocation {
+                    program_id: program_id.to_string(),
+                    depth,
+                    consumed: None,
+                    budget: None,
+                    success: None,
+                });
+                line = LogLine {
+                    invocation: stack.last().copied(),
+                    program_id: Some(program_id.to_string()),
+                    depth,
+                    event: LogEvent::Invoke,
+                };
+            } else if let Some((consumed, budget)) = parse_consumed(event) {
+                if let Some(idx) = current.filter(|&idx| invocations[idx].program_id == program_id)
+                {
+                    invocations[idx].consumed = Some(consumed);
+                    invocations[idx].budget = Some(budget);
+                }
+                line.event = LogEvent::Consumed { consumed, budget };
+            } else if event == "success" {
+                if let Some(idx) = stack.pop() {
+                    invocations[idx].success = Some(true);
+                }
+                line.event = LogEvent::Success;
+            } else if let Some(error) = event.strip_prefix("failed: ") {
+                if let Some(idx) = stack.pop() {
+                    invocations[idx].success = Some(false);
+                }
+                line.event = LogEvent::Failed {
+                    error: error.to_string(),
+                };
+            }
+        }
+
+        lines.push(line);
+    }
+
+    (lines, invocations)
+}
+
+/// Parses "consumed X of Y compute units"
+fn parse_consumed(event: &str) -> Option<(u64, u64)> {
+    let rest = event
+        .strip_prefix("consumed ")?
+        .strip_suffix(" compute units")?;
+    let (consumed, budget) = rest.split_once(" of ")?;
+    Some((consumed.parse().ok()?, budget.parse().ok()?))
+}
+
+/// Decodes base64 program data to hex, leaving it as is if it is not base64
+fn base64_to_hex(data: &str) -> String {
+    match base64::engine::general_purpose::STANDARD.decode(data) {
+        Ok(bytes) => to_hex(&bytes),
+        Err(_) => data.to_string(),
+    }
+}
+
+/// Colors assigned to programs in order of first appearance
+const PROGRAM_COLORS: &[Color] = &[
+    Color::Cyan,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Blue,
+    Color::BrightCyan,
+    Color::BrightMagenta,
+    Color::BrightYellow,
+    Color::BrightBlue,
+];
+
+/// Renders logs indented by invoke depth, with one color per program. All
+/// lines emitted by a failed invocation are highlighted in red. `width` is
+/// used to wrap long lines.
+pub fn logs_table(lines: &[LogLine], invocations: &[Invocation], width: usize) -> Table {
+    let mut table = Table::new();
+    table.set_titles(row![c->"Program Logs"]);
+
+    let mut programs: Vec<&str> = vec![];
+    for line in lines {
+        let program_label = line.program_id.as_deref().map(|id| {
+            id.parse()
+                .ok()
+                .and_then(|program_id| known_program_name(&program_id))
+                .unwrap_or(id)
+        });
+
+        let indent = "  ".repeat(line.depth.saturating_sub(1) as usize);
+        let text = match &line.event {
+            LogEvent::Invoke => format!(
+                "▶ {} invoke [{}]",
+                program_label.unwrap_or_default(),
+                line.depth
+            ),
+            LogEvent::Log { message } => format!("  {message}"),
+            LogEvent::Data { data } => format!("  data: {}", data.join(" ")),
+            LogEvent::Return { data } => format!("  return: {data}"),
+            LogEvent::Consumed { consumed, budget } => {
+                format!("  consumed {consumed} of {budget} compute units")
+            }
+            LogEvent::Success => format!("◀ {} success", program_label.unwrap_or_default()),
+            LogEvent::Failed { error } => {
+                format!("◀ {} failed: {error}", program_label.unwrap_or_default())
+            }
+            LogEvent::Other { message } => format!("  {message}"),
+        };
+
+        // Wrap, keeping the indentation on continuation lines
+        let wrapped = insert_newlines(&text, width.saturating_sub(indent.len()).max(1))
+            .lines()
+            .map(|l| format!("{indent}{l}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let failed = line
+            .invocation
+            .is_some_and(|idx| invocations[idx].success == Some(false));
+        let colored = if failed {
+            wrapped.red().bold()
+        } else if let Some(program_id) = line.program_id.as_deref() {
+            let idx = match programs.iter().position(|&p| p == program_id) {
+                Some(idx) => idx,
+                None => {
+                    programs.push(program_id);
+                    programs.len() - 1
+                }
+            };
+            wrapped.color(PROGRAM_COLORS[idx % PROGRAM_COLORS.len()])
+        } else {
+            wrapped.normal()
+        };
+
+        table.add_row(row![colored]);
+    }
+
+    table
+}
@@ -3,14 +3,16 @@ use std::borrow::Cow;
 use colored::{ColoredString, Colorize};
 use num_format::{Locale, ToFormattedString};
 use prettytable::{format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR, row, Table};
+use serde::Serialize;
 use solana_client::{
     nonblocking::rpc_client::RpcClient as Client, rpc_config::RpcTransactionConfig,
+    rpc_request::RpcRequest,
 };
 use solana_sdk::{
-    address_lookup_table::state::AddressLookupTable,
     hash::Hash,
     instruction::AccountMeta,
     message::VersionedMessage,
+    signature::Signature,
     transaction::{TransactionVersion, VersionedTransaction},
 };
 use solana_transaction_status::{
@@ -19,39 +21,79 @@ use solana_transaction_status::{
 };
 
 use crate::{
-    utils::{get_network, insert_newlines},
+    config::confirmed_commitment,
+    error::{ExplorerError, Result},
+    transaction::{
+        call_tree::{build_call_tree, call_tree_table, CallTreeEntry},
+        errors::{ErrorTables, NamedError, TransactionErrorDetails},
+        instructions::{parse_instructions, InstructionView, ParsedInstruction},
+        loaded_addresses::{resolve_loaded_addresses, LoadedAddressesSource},
+        logs::{logs_table, parse_logs, Invocation, LogLine},
+        token_balances::{
+            resolve_symbols, token_balance_changes, token_balances_table, TokenBalanceChange,
+            TokenBalanceView,
+        },
+    },
+    utils::{insert_newlines, print_json, OutputFormat},
     Transaction,
 };
 
-pub async fn handler(rpc_url: String, transaction: Transaction) {
-    // Build RPC Client
-    let client = Client::new(get_network(&rpc_url));
+pub mod call_tree;
+pub mod errors;
+pub mod instructions;
+pub mod loaded_addresses;
+pub mod logs;
+pub mod token_balances;
+
+pub async fn handler(client: Client, output: OutputFormat, transaction: Transaction) -> Result<()> {
+    // Load user supplied custom error tables
+    let error_tables = ErrorTables::load(&transaction.error_tables)?;
 
     // Fetch transaction
-    let fetched_transaction = client
-        .get_transaction_with_config(
-            &transaction.signature,
-            RpcTransactionConfig {
-                encoding: Some(UiTransactionEncoding::Base58),
-                max_supported_transaction_version: Some(0),
-                ..Default::default()
-            },
-        )
-        .await
-        .unwrap();
+    let fetched_transaction = fetch_transaction(&client, &transaction.signature).await?;
 
     // Parse transaction
-    let parsed_transaction = parse_transaction(fetched_transaction, &client)
-        .await
-        .unwrap();
+    let parsed_transaction = parse_transaction(fetched_transaction, &client, &error_tables).await?;
+
+    match output {
+        OutputFormat::Table => parsed_transaction.view(),
+        OutputFormat::Json => print_json(&parsed_transaction.to_view()),
+    }
 
-    parsed_transaction.view();
+    Ok(())
+}
+
+/// Fetches a transaction, distinguishing transactions that are not found
+/// (e.g. unconfirmed or pruned) from rpc failures.
+pub async fn fetch_transaction(
+    client: &Client,
+    signature: &Signature,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base58),
+        commitment: Some(confirmed_commitment(client)),
+        max_supported_transaction_version: Some(0),
+    };
+
+    // The rpc returns null for unknown transactions
+    client
+        .send::<Option<EncodedConfirmedTransactionWithStatusMeta>>(
+            RpcRequest::GetTransaction,
+            serde_json::json!([signature.to_string(), config]),
+        )
+        .await?
+        .ok_or_else(|| {
+            ExplorerError::NotFound(format!(
+                "transaction {signature} not found (it may be unconfirmed, or pruned by the rpc node)"
+            ))
+        })
 }
 
 async fn parse_transaction(
     transaction: EncodedConfirmedTransactionWithStatusMeta,
     client: &Client,
-) -> Option<ParsedTransaction> {
+    error_tables: &ErrorTables,
+) -> Result<ParsedTransaction> {
     let EncodedConfirmedTransactionWithStatusMeta {
         slot,
         transaction:
@@ -63,30 +105,26 @@ async fn parse_transaction(
         block_time,
     } = transaction;
 
-    let Some(meta) = meta else {
-        // TODO
-        return None;
-    };
-
-    let Some(time) = block_time else {
-        // TODO
-        return None;
-    };
+    let meta = meta.ok_or_else(|| {
+        ExplorerError::NotFound("transaction status metadata not found".to_string())
+    })?;
 
-    let Some(version) = version else {
-        // TODO
-        return None;
-    };
+    // Versions are always returned since we set max_supported_transaction_version
+    let version = version.ok_or_else(|| {
+        ExplorerError::UnsupportedVersion("rpc did not report the transaction version".to_string())
+    })?;
 
     // Decode transaction
     let VersionedTransaction {
-        signatures: _,
+        signatures,
         message,
     } = encoded_transaction
         .decode()
-        .expect("TODO: failed to decode error");
+        .ok_or_else(|| ExplorerError::Decode("transaction".to_string()))?;
 
     // Get accounts
+    let mut loaded_addresses_source = None;
+    let mut warnings = vec![];
     let accounts = match &message {
         VersionedMessage::Legacy(legacy) => {
             // Legacy only has static accounts
@@ -122,53 +160,64 @@ async fn parse_transaction(
                 })
                 .collect();
 
-            // Then, try account lookups
-            // (this may fail if lookup table is deactivated and closed)
-            if let Some(lookups) = message.address_table_lookups() {
-                for lookup in lookups {
-                    // Fetch and try deserialize
-                    match client
-                        .get_account_data(&lookup.account_key)
-                        .await
-                        .as_deref()
-                        .map(AddressLookupTable::deserialize)
-                    {
-                        // If fetch + deserialize succeeded, perform lookups.
-                        // Lookups cannot be signers.
-                        Ok(Ok(alt)) => {
-                            // Write accounts
-                            for &idx in &lookup.writable_indexes {
-                                accounts.push(AccountMeta::new(alt.addresses[idx as usize], false))
-                            }
-
-                            // Read accounts
-                            for &idx in &lookup.readonly_indexes {
-                                accounts.push(AccountMeta::new_readonly(
-                                    alt.addresses[idx as usize],
-                                    false,
-                                ))
-                            }
-                        }
-
-                        e => {
-                            println!(
-                                "failed to perform lookup for table {}: {e:#?}",
-                                lookup.account_key
-                            );
-                        }
-                    }
-                }
+            // Then, loaded accounts. These are taken from the metadata since
+            // the lookup tables may have changed since the transaction.
+            // Lookups cannot be signers.
+            if !v0.address_table_lookups.is_empty() {
+                let loaded =
+                    resolve_loaded_addresses(&v0.address_table_lookups, &meta, client).await;
+                accounts.extend(
+                    loaded
+                        .writable
+                        .into_iter()
+                        .map(|address| AccountMeta::new(address, false)),
+                );
+                accounts.extend(
+                    loaded
+                        .readonly
+                        .into_iter()
+                        .map(|address| AccountMeta::new_readonly(address, false)),
+                );
+                loaded_addresses_source = Some(loaded.source);
+                warnings.extend(loaded.warnings);
             }
 
             accounts
         }
     };
 
-    // First, static accounts
-    Some(ParsedTransaction {
+    // Decode instructions, attaching cpis to their top level instruction
+    let instructions =
+        parse_instructions(message.instructions(), &meta.inner_instructions, &accounts);
+
+    // Match cpis up with their compute units from the logs
+    let log_messages: Option<&Vec<String>> = meta.log_messages.as_ref().into();
+    let (log_lines, invocations) = parse_logs(log_messages.map(Vec::as_slice).unwrap_or_default());
+    let call_tree = build_call_tree(&instructions, &invocations);
+
+    // Decode the error, if any
+    let error = meta
+        .err
+        .as_ref()
+        .map(|err| TransactionErrorDetails::new(err, &instructions, &invocations, error_tables));
+
+    // Pair up token balances and look up their symbols
+    let mut token_balances = token_balance_changes(&meta, &accounts);
+    resolve_symbols(&mut token_balances, client).await;
+
+    Ok(ParsedTransaction {
+        signature: signatures[0],
         meta,
-        time,
+        time: block_time,
         accounts,
+        loaded_addresses_source,
+        warnings,
+        instructions,
+        call_tree,
+        error,
+        log_lines,
+        invocations,
+        token_balances,
         slot,
         version,
         blockhash: *message.recent_blockhash(),
@@ -176,16 +225,114 @@ async fn parse_transaction(
 }
 
 pub struct ParsedTransaction {
+    signature: Signature,
     meta: UiTransactionStatusMeta,
     accounts: Vec<AccountMeta>,
+    /// Where addresses loaded from lookup tables came from, if any
+    loaded_addresses_source: Option<LoadedAddressesSource>,
+    warnings: Vec<String>,
+    instructions: Vec<ParsedInstruction>,
+    call_tree: Vec<CallTreeEntry>,
+    error: Option<TransactionErrorDetails>,
+    log_lines: Vec<LogLine>,
+    invocations: Vec<Invocation>,
+    token_balances: Vec<TokenBalanceChange>,
     blockhash: Hash,
     slot: u64,
     version: TransactionVersion,
-    time: i64,
+    time: Option<i64>,
+}
+
+/// Serializable view model of a [`ParsedTransaction`]
+#[derive(Serialize)]
+pub struct TransactionView<'a> {
+    pub signature: String,
+    pub success: bool,
+    pub error: Option<&'a TransactionErrorDetails>,
+    pub slot: u64,
+    pub timestamp: Option<i64>,
+    pub fee: u64,
+    pub version: String,
+    pub recent_blockhash: String,
+    pub compute_units_consumed: Option<u64>,
+    pub accounts: Vec<TransactionAccountView>,
+    pub loaded_addresses_source: Option<LoadedAddressesSource>,
+    /// Inconsistencies found while parsing, e.g. in the loaded addresses
+    pub warnings: &'a [String],
+    pub token_balances: Vec<TokenBalanceView>,
+    pub instructions: Vec<InstructionView>,
+    pub call_tree: &'a [CallTreeEntry],
+    pub log_messages: Vec<String>,
+    /// Structured logs, attributed to the program that emitted them
+    pub logs: &'a [LogLine],
+}
+
+#[derive(Serialize)]
+pub struct TransactionAccountView {
+    pub pubkey: String,
+    pub signer: bool,
+    pub writable: bool,
+    pub pre_balance: u64,
+    pub post_balance: u64,
 }
 
 impl ParsedTransaction {
+    fn to_view(&self) -> TransactionView<'_> {
+        let accounts = self
+            .accounts
+            .iter()
+            .zip(self.meta.pre_balances.iter().zip(&self.meta.post_balances))
+            .map(
+                |(account, (&pre_balance, &post_balance))| TransactionAccountView {
+                    pubkey: account.pubkey.to_string(),
+                    signer: account.is_signer,
+                    writable: account.is_writable,
+                    pre_balance,
+                    post_balance,
+                },
+            )
+            .collect();
+
+        let log_messages: Option<Vec<String>> = self.meta.log_messages.clone().into();
+
+        TransactionView {
+            signature: self.signature.to_string(),
+            success: self.meta.status.is_ok(),
+            error: self.error.as_ref(),
+            slot: self.slot,
+            timestamp: self.time,
+            fee: self.meta.fee,
+            version: format_version(&self.version).into_owned(),
+            recent_blockhash: self.blockhash.to_string(),
+            compute_units_consumed: self.meta.compute_units_consumed.clone().into(),
+            accounts,
+            loaded_addresses_source: self.loaded_addresses_source,
+            warnings: &self.warnings,
+            token_balances: self
+                .token_balances
+                .iter()
+                .map(TokenBalanceChange::to_view)
+                .collect(),
+            instructions: self
+                .instructions
+                .iter()
+                .map(ParsedInstruction::to_view)
+                .collect(),
+            call_tree: &self.call_tree,
+            log_messages: log_messages.unwrap_or_default(),
+            logs: &self.log_lines,
+        }
+    }
+
     fn view(self) {
+        // Get terminal size for newlines
+        use terminal_size::{terminal_size, Width};
+        let size = terminal_size();
+        let width = size
+            .map(|(Width(w), _height)| w as usize)
+            .unwrap_or(32)
+            .saturating_sub(6);
+
         // Create status table
         let mut status_table = Table::new();
         status_table.set_titles(row![
@@ -196,17 +343,39 @@ impl ParsedTransaction {
         } else {
             "FAILURE".red()
         };
-        let cus: u64 = Option::unwrap(self.meta.compute_units_consumed.into());
+        let cus: Option<u64> = self.meta.compute_units_consumed.clone().into();
         status_table.add_row(row!["Result", result]);
+        if let Some(error) = &self.error {
+            add_error_rows(&mut status_table, error, &self.instructions);
+        }
         status_table.add_row(row!["Slot", self.slot]);
-        status_table.add_row(row!["Timestamp", self.time]);
+        status_table.add_row(row![
+            "Timestamp",
+            self.time
+                .map(|time| time.to_string())
+                .unwrap_or("Unknown".to_string())
+        ]);
         status_table.add_row(row!["Fee", format_fee(self.meta.fee)]);
         status_table.add_row(row!["Version", format_version(&self.version)]);
         status_table.add_row(row!["Recent Blockhash", self.blockhash.to_string()]);
         status_table.add_row(row![
             "Compute Units Consumed",
-            cus.to_formatted_string(&Locale::en)
+            cus.map(|cus| cus.to_formatted_string(&Locale::en))
+                .unwrap_or("Unknown".to_string())
         ]);
+        if let Some(source) = self.loaded_addresses_source {
+            let source = match source {
+                LoadedAddressesSource::Metadata => "Transaction metadata".normal(),
+                LoadedAddressesSource::LiveLookup => "Current lookup tables".yellow(),
+            };
+            status_table.add_row(row!["Loaded Addresses", source]);
+        }
+        for warning in &self.warnings {
+            status_table.add_row(row![
+                "Warning".yellow(),
+                insert_newlines(warning, width.saturating_sub(24).max(1)).yellow()
+            ]);
+        }
 
         // Create accounts table
         let mut accounts_table = Table::new();
@@ -246,40 +415,82 @@ impl ParsedTransaction {
             ]);
         }
 
-        // TODO: Token Accounts pre/post
-        let mut _token_accounts = Table::new();
-
-        // TODO: Instructions table
-        let mut _instructions_table = Table::new();
+        // Create token balances table
+        let token_balances_table = token_balances_table(&self.token_balances);
+
+        // Create instructions table, with cpis listed below their top level
+        // instruction and indented by invocation depth
+        let mut instructions_table = Table::new();
+        instructions_table.set_titles(row![c->"Instructions"]);
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            let label = format!("#{}", i + 1);
+            instructions_table.add_row(row![instruction.table(&label, width)]);
+            for (j, inner) in instruction.inner_instructions.iter().enumerate() {
+                let indent = "  ".repeat(inner.stack_height.saturating_sub(1) as usize);
+                let label = format!("{indent}↳ #{}.{}", i + 1, j + 1);
+                instructions_table.add_row(row![inner.table(&label, width)]);
+            }
+        }
 
-        // Get terminal size for newlines
-        use terminal_size::{terminal_size, Width};
-        let size = terminal_size();
-        let width = size
-            .map(|(Width(w), _height)| w as usize)
-            .unwrap_or(32)
-            .saturating_sub(6);
+        // Create call tree table
+        let call_tree_table = call_tree_table(&self.call_tree);
 
         // Create logs table
-        let mut logs_table = Table::new();
-        logs_table.set_titles(row![c->"Program Logs"]);
-        let opt_log_messages: Option<Vec<String>> = self.meta.log_messages.into();
-        if let Some(log_msgs) = opt_log_messages {
-            for log in log_msgs {
-                logs_table.add_row(row![insert_newlines(&log, width)]);
-            }
-        }
+        let logs_table = logs_table(&self.log_lines, &self.invocations, width);
 
         // Print the table to stdout
         let mut table_of_tables = Table::new();
         table_of_tables.add_row(row![c->status_table]);
         table_of_tables.add_row(row![c->accounts_table]);
+        if !self.token_balances.is_empty() {
+            table_of_tables.add_row(row![c->token_balances_table]);
+        }
+        table_of_tables.add_row(row![c->instructions_table]);
+        table_of_tables.add_row(row![c->call_tree_table]);
         table_of_tables.add_row(row![c->logs_table]);
         table_of_tables.set_format(*FORMAT_NO_BORDER_LINE_SEPARATOR);
         table_of_tables.printstd();
     }
 }
 
+fn add_error_rows(
+    table: &mut Table,
+    error: &TransactionErrorDetails,
+    instructions: &[ParsedInstruction],
+) {
+    table.add_row(row!["Error", error.error.red()]);
+
+    if let Some(index) = error.instruction_index {
+        let program = instructions
+            .get(index as usize)
+            .map(ParsedInstruction::program_label)
+            .unwrap_or_default();
+        table.add_row(row![
+            "Failed Instruction",
+            format!("#{} {program}", index + 1)
+        ]);
+    }
+
+    if let Some(program_id) = &error.program_id {
+        table.add_row(row!["Failing Program", program_id]);
+    }
+
+    if let Some(code) = error.custom_code {
+        let description = match &error.custom_error {
+            Some(NamedError {
+                name,
+                message: Some(message),
+            }) => format!("{code} (0x{code:x}): {name} - {message}"),
+            Some(NamedError {
+                name,
+                message: None,
+            }) => format!("{code} (0x{code:x}): {name}"),
+            None => format!("{code} (0x{code:x}): unknown"),
+        };
+        table.add_row(row!["Error Code", description.red()]);
+    }
+}
+
 #[inline(always)]
 fn format_fee(fee_lamports: u64) -> String {
     let floating = fee_lamports as f64 / 1e9;
@@ -0,0 +1,178 @@
+//! Token balance changes from a transaction's pre/post token balances
+
+use std::collections::BTreeMap;
+
+use colored::{ColoredString, Colorize};
+use futures::StreamExt;
+use prettytable::{row, Table};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient as Client;
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+use solana_transaction_status::{UiTransactionStatusMeta, UiTransactionTokenBalance};
+
+use crate::{account::system::get_symbol_for_mint, utils::display_balance};
+
+pub struct TokenBalanceChange {
+    pub account: Pubkey,
+    pub owner: Option<String>,
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub decimals: u8,
+    pub pre: u64,
+    pub post: u64,
+}
+
+impl TokenBalanceChange {
+    pub fn delta(&self) -> i128 {
+        self.post as i128 - self.pre as i128
+    }
+
+    pub fn to_view(&self) -> TokenBalanceView {
+        TokenBalanceView {
+            account: self.account.to_string(),
+            owner: self.owner.clone(),
+            mint: self.mint.clone(),
+            symbol: self.symbol.clone(),
+            decimals: self.decimals,
+            pre_amount: self.pre.to_string(),
+            post_amount: self.post.to_string(),
+            delta: self.delta().to_string(),
+        }
+    }
+}
+
+/// Serializable view model of a [`TokenBalanceChange`]. Amounts are raw
+/// (integer) token amounts.
+#[derive(Serialize)]
+pub struct TokenBalanceView {
+    pub account: String,
+    pub owner: Option<String>,
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub decimals: u8,
+    pub pre_amount: String,
+    pub post_amount: String,
+    pub delta: String,
+}
+
+/// Pairs up pre and post token balances by account. Accounts that were
+/// created or closed by the transaction have a zero pre or post balance.
+pub fn token_balance_changes(
+    meta: &UiTransactionStatusMeta,
+    accounts: &[AccountMeta],
+) -> Vec<TokenBalanceChange> {
+    let pre: Option<&Vec<UiTransactionTokenBalance>> = meta.pre_token_balances.as_ref().into();
+    let post: Option<&Vec<UiTransactionTokenBalance>> = meta.post_token_balances.as_ref().into();
+
+    let mut changes: BTreeMap<u8, TokenBalanceChange> = BTreeMap::new();
+    let mut record = |balance: &UiTransactionTokenBalance, is_post: bool| {
+        let Some(account) = accounts.get(balance.account_index as usize) else {
+            return;
+        };
+        let amount = balance.ui_token_amount.amount.parse().unwrap_or_default();
+        let change = changes
+            .entry(balance.account_index)
+            .or_insert_with(|| TokenBalanceChange {
+                account: account.pubkey,
+                owner: Option::<String>::from(balance.owner.clone()),
+                mint: balance.mint.clone(),
+                symbol: None,
+                decimals: balance.ui_token_amount.decimals,
+                pre: 0,
+                post: 0,
+            });
+        if is_post {
+            change.post = amount;
+        } else {
+            change.pre = amount;
+        }
+    };
+    for balance in pre.into_iter().flatten() {
+        record(balance, false);
+    }
+    for balance in post.into_iter().flatten() {
+        record(balance, true);
+    }
+
+    changes.into_values().collect()
+}
+
+/// Number of mints whose symbols are fetched concurrently
+const CONCURRENT_SYMBOL_FETCHES: usize = 10;
+
+/// Resolves symbols for all mints in `changes`, fetching each mint once
+pub async fn resolve_symbols(changes: &mut [TokenBalanceChange], client: &Client) {
+    let symbols =
+        resolve_mint_symbols(changes.iter().map(|change| change.mint.as_str()), client).await;
+    for change in changes {
+        change.symbol = symbols.get(&change.mint).cloned().flatten();
+    }
+}
+
+/// Fetches the symbol of each distinct mint, keyed by mint
+pub async fn resolve_mint_symbols<'a>(
+    mints: impl IntoIterator<Item = &'a str>,
+    client: &Client,
+) -> BTreeMap<String, Option<String>> {
+    let mut mints: Vec<&str> = mints.into_iter().collect();
+    mints.sort_unstable();
+    mints.dedup();
+
+    let symbol_futures = mints.into_iter().map(|mint| async move {
+        let symbol = match mint.parse::<Pubkey>() {
+            Ok(key) => get_symbol_for_mint(&key, client).await,
+            Err(_) => None,
+        };
+        (mint.to_string(), symbol)
+    });
+    futures::stream::iter(symbol_futures)
+        .buffer_unordered(CONCURRENT_SYMBOL_FETCHES)
+        .collect()
+        .await
+}
+
+pub fn token_balances_table(changes: &[TokenBalanceChange]) -> Table {
+    let mut table = Table::new();
+    table.set_titles(row![
+        c->"Token Account",
+        c->"Owner",
+        c->"Token",
+        c->"Pre-Balance",
+        c->"Post-Balance",
+        c->"Change"
+    ]);
+
+    for change in changes {
+        let decimals = change.decimals as usize;
+        table.add_row(row![
+            change.account.to_string(),
+            change.owner.as_deref().unwrap_or("Unknown"),
+            change.symbol.as_deref().unwrap_or(change.mint.as_str()),
+            format_token_pre_post(display_balance(change.pre, decimals), change),
+            format_token_pre_post(display_balance(change.post, decimals), change),
+            format_token_pre_post(format_delta(change.delta(), decimals), change),
+        ]);
+    }
+
+    table
+}
+
+fn format_delta(delta: i128, decimals: usize) -> String {
+    let abs = display_balance(delta.unsigned_abs() as u64, decimals);
+    match delta {
+        d if d > 0 => format!("+{abs}"),
+        d if d < 0 => format!("-{abs}"),
+        _ => abs,
+    }
+}
+
+#[inline(always)]
+fn format_token_pre_post(current: String, change: &TokenBalanceChange) -> ColoredString {
+    if change.pre > change.post {
+        current.red()
+    } else if change.pre < change.post {
+        current.green()
+    } else {
+        current.into()
+    }
+}
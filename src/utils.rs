@@ -1,3 +1,23 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// How command results are written to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human readable tables
+    Table,
+    /// A single JSON document per result, suitable for piping into jq
+    Json,
+}
+
+/// Prints any serializable view model as pretty JSON to stdout
+pub fn print_json<T: Serialize>(value: &T) {
+    // View models only contain strings, numbers, and collections thereof
+    let json = serde_json::to_string_pretty(value).expect("view models are always serializable");
+    println!("{json}");
+}
+
 pub fn get_network(network_str: &str) -> String {
     match network_str {
         "devnet" | "dev" | "d" => "https://api.devnet.solana.com",
@@ -10,6 +30,8 @@ pub fn get_network(network_str: &str) -> String {
     .to_string()
 }
 
+// `is_multiple_of` needs Rust 1.87, newer than the toolchains this builds with
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
 pub fn display_balance(atoms: u64, decimals: usize) -> String {
     let atoms_str = atoms.to_string();
     let len = atoms_str.len();
@@ -44,6 +66,11 @@ pub fn display_balance(atoms: u64, decimals: usize) -> String {
     result
 }
 
+/// Formats basis points as a percentage, e.g. `5.25% (525 bps)`
+pub fn format_basis_points(basis_points: i64) -> String {
+    format!("{:.2}% ({basis_points} bps)", basis_points as f64 / 100.0)
+}
+
 #[inline(always)]
 pub fn insert_newlines(s: &str, n: usize) -> String {
     let mut result = String::new();
@@ -60,3 +87,14 @@ pub fn insert_newlines(s: &str, n: usize) -> String {
 
     result
 }
+
+/// Lowercase hex encoding of arbitrary bytes
+pub fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    let mut result = String::with_capacity(2 * bytes.len());
+    for byte in bytes {
+        write!(result, "{byte:02x}").unwrap();
+    }
+    result
+}